@@ -1,94 +1,391 @@
-use crate::api::dto::{PlayerGameDto, QuestionDataDto, QuestionType, RoundDto, RoundStatsDto};
+use crate::api::dto::{
+    AnswerTimerDto, AuctionStateDto, FinalRoundDto, GameHistoryDto, GameStateDto, GameStatsDto, HostNotesDto, PlayerGameDto, QuestionDataDto, QuestionSceneDto,
+    QuestionType, RoundDto, RoundStatsDto, RoundTimerDto, StateTransitionDto, TieBreakDto,
+};
 use crate::api::mapper::*;
-use crate::core::game_entities::{game, GameplayError};
-use crate::hub_comm::hw::hw_hub_manager::HubManagerError;
-use tauri::command;
+use crate::core::auction::AuctionBidType;
+use crate::core::game_engine::{CommandOutput, EngineCommand, GameEngine};
+use crate::core::game_entities::GameplayError;
+use crate::core::game_journal::{replay_journal_file, GameJournalError};
+use crate::core::score_adjustment::ScoreAdjustmentType;
+use crate::core::tie_break::TieBreakSource;
+use crate::game_pack::pack_content_entities::QuestionId;
+use std::path::Path;
+use std::time::Duration;
+use tauri::{command, State};
 
 #[command]
-pub fn fetch_players() -> Vec<PlayerGameDto> {
-    let vec = map_players_to_player_game_dto(game().fetch_players());
+pub fn fetch_players(engine: State<'_, GameEngine>) -> Vec<PlayerGameDto> {
+    let vec = map_players_to_player_game_dto(engine.context().fetch_players());
     log::trace!("Players: {:#?}", vec);
     vec
 }
 
 #[command]
-pub fn fetch_round() -> RoundDto {
-    let round_dto = map_round_to_dto(game().get_current_round());
+pub fn fetch_round(engine: State<'_, GameEngine>) -> RoundDto {
+    let game = engine.context();
+    let round_dto = map_round_to_dto(game.current.round_index, game.get_current_round());
     log::trace!("{round_dto:#?}");
     round_dto
 }
 
 #[command]
-pub fn get_question_data(topic: String, price: i32) -> Result<QuestionDataDto, GameplayError> {
-    let (question, q_num) = game().get_pack_question(&topic, &price).map_err(|e| {
-        log::error!("Can't get question data: {:#?}", e);
+pub fn get_question_data(
+    engine: State<'_, GameEngine>,
+    id: QuestionId,
+) -> Result<QuestionDataDto, GameplayError> {
+    let output = execute(&engine, EngineCommand::SelectQuestion { id })?;
+    map_question_output_to_dto(output)
+}
+
+/// Answer illustration of the last question, once it is closed
+#[command]
+pub fn reveal_answer_scene(
+    engine: State<'_, GameEngine>,
+) -> Result<Vec<QuestionSceneDto>, GameplayError> {
+    let game = engine.context();
+    let answer_scene = game.get_answer_scene().map_err(|e| {
+        log::error!("{:?}", e);
         e.current_context().clone()
     })?;
+    Ok(map_atoms_to_scene_dto(answer_scene))
+}
 
-    Ok(map_question_to_question_dto(topic, question, q_num))
+/// Host-only notes for judging the current question
+#[command]
+pub fn fetch_host_notes(engine: State<'_, GameEngine>) -> HostNotesDto {
+    map_host_notes_to_dto(engine.context().get_host_notes())
 }
 
 #[command]
-pub fn allow_answer() -> Result<(), HubManagerError> {
-    game().allow_answer().map_err(|e| {
-        log::error!("{:?}", e);
-        e.current_context().clone()
-    })
+pub fn allow_answer(engine: State<'_, GameEngine>) -> Result<(), GameplayError> {
+    execute(&engine, EngineCommand::AllowAnswer)?;
+    Ok(())
 }
 
 #[command]
-pub fn get_fastest_click() -> Result<i32, GameplayError> {
-    let id = game().get_fastest_click_player_id().map_err(|e| {
-        log::error!("{:?}", e);
+pub fn fetch_answer_timer(engine: State<'_, GameEngine>) -> Option<AnswerTimerDto> {
+    engine
+        .context()
+        .current
+        .answer_timer
+        .as_ref()
+        .map(map_answer_timer_to_dto)
+}
+
+#[command]
+pub fn extend_answer_timer(
+    engine: State<'_, GameEngine>,
+    extra_seconds: u64,
+) -> Result<(), GameplayError> {
+    let extra_time = Duration::from_secs(extra_seconds);
+    execute(&engine, EngineCommand::ExtendAnswerTimer { extra_time })?;
+    Ok(())
+}
+
+#[command]
+pub fn cancel_answer_timer(engine: State<'_, GameEngine>) -> Result<(), GameplayError> {
+    execute(&engine, EngineCommand::CancelAnswerTimer)?;
+    Ok(())
+}
+
+#[command]
+pub fn answer_question(
+    engine: State<'_, GameEngine>,
+    answered_correctly: bool,
+) -> Result<bool, GameplayError> {
+    log::debug!("Answered correctly: {answered_correctly}");
+
+    match execute(&engine, EngineCommand::JudgeAnswer { answered_correctly })? {
+        CommandOutput::AnswerJudged { retry } => Ok(retry),
+        output => unexpected_output(output),
+    }
+}
+
+#[command]
+pub fn fetch_game_history(engine: State<'_, GameEngine>) -> GameHistoryDto {
+    map_history_to_dto(&engine.context().history)
+}
+
+#[command]
+pub fn undo_last_action(engine: State<'_, GameEngine>) -> Result<GameHistoryDto, GameplayError> {
+    execute(&engine, EngineCommand::Undo)?;
+    Ok(map_history_to_dto(&engine.context().history))
+}
+
+#[command]
+pub fn redo_last_action(engine: State<'_, GameEngine>) -> Result<GameHistoryDto, GameplayError> {
+    execute(&engine, EngineCommand::Redo)?;
+    Ok(map_history_to_dto(&engine.context().history))
+}
+
+#[command]
+pub fn has_next_question(engine: State<'_, GameEngine>) -> bool {
+    engine.context().has_next_question()
+}
+
+#[command]
+pub fn finish_question_prematurely(engine: State<'_, GameEngine>) -> Result<(), GameplayError> {
+    execute(&engine, EngineCommand::FinishQuestion)?;
+    Ok(())
+}
+
+#[command]
+pub fn init_next_round(engine: State<'_, GameEngine>) -> Result<(), GameplayError> {
+    execute(&engine, EngineCommand::StartNextRound)?;
+    Ok(())
+}
+
+#[command]
+pub fn send_pip_victim(engine: State<'_, GameEngine>, victim_id: i32) -> Result<(), GameplayError> {
+    log::debug!("Victim id is: {}", victim_id);
+
+    let player_id = victim_id as u8;
+    execute(&engine, EngineCommand::PassPigInPoke { player_id })?;
+    Ok(())
+}
+
+#[command]
+pub fn fetch_auction_state(
+    engine: State<'_, GameEngine>,
+) -> Result<AuctionStateDto, GameplayError> {
+    let game = engine.context();
+    let auction = game.get_auction().map_err(|e| {
+        log::error!("Can't get auction state: {:?}", e);
         e.current_context().clone()
     })?;
-    Ok(id as i32)
+
+    Ok(map_auction_to_auction_state_dto(auction, &game.players))
 }
 
 #[command]
-pub fn answer_question(answered_correctly: bool) -> Result<bool, GameplayError> {
-    log::debug!("Answered correctly: {answered_correctly}");
+pub fn place_auction_bid(
+    engine: State<'_, GameEngine>,
+    player_id: i32,
+    bid_type: AuctionBidType,
+    amount: i32,
+) -> Result<AuctionStateDto, GameplayError> {
+    log::debug!("Player {} bids {:?}: {}", player_id, bid_type, amount);
+
+    let command = EngineCommand::PlaceAuctionBid {
+        player_id: player_id as u8,
+        bid_type,
+        amount,
+    };
+    execute(&engine, command)?;
 
-    game().answer_question(answered_correctly).map_err(|e| {
-        log::error!("Failed to answer question: {:?}", e);
+    fetch_auction_state(engine)
+}
+
+#[command]
+pub fn fetch_final_round(engine: State<'_, GameEngine>) -> Result<FinalRoundDto, GameplayError> {
+    let game = engine.context();
+    let final_round = game.get_final_round().map_err(|e| {
+        log::error!("Can't get final round: {:?}", e);
         e.current_context().clone()
-    })
+    })?;
+
+    Ok(map_final_round_to_dto(final_round, &game.players))
+}
+
+#[command]
+pub fn eliminate_final_theme(
+    engine: State<'_, GameEngine>,
+    player_id: i32,
+    theme_index: usize,
+) -> Result<FinalRoundDto, GameplayError> {
+    let player_id = player_id as u8;
+    execute(
+        &engine,
+        EngineCommand::EliminateFinalTheme {
+            player_id,
+            theme_index,
+        },
+    )?;
+
+    fetch_final_round(engine)
+}
+
+#[command]
+pub fn place_final_wager(
+    engine: State<'_, GameEngine>,
+    player_id: i32,
+    wager: i32,
+) -> Result<FinalRoundDto, GameplayError> {
+    let player_id = player_id as u8;
+    execute(&engine, EngineCommand::PlaceFinalWager { player_id, wager })?;
+
+    fetch_final_round(engine)
+}
+
+#[command]
+pub fn get_final_question(engine: State<'_, GameEngine>) -> Result<QuestionDataDto, GameplayError> {
+    let output = execute(&engine, EngineCommand::ShowFinalQuestion)?;
+    map_question_output_to_dto(output)
 }
 
 #[command]
-pub fn has_next_question() -> bool {
-    game().has_next_question()
+pub fn submit_final_answer(
+    engine: State<'_, GameEngine>,
+    player_id: i32,
+    answer: String,
+) -> Result<FinalRoundDto, GameplayError> {
+    let player_id = player_id as u8;
+    execute(&engine, EngineCommand::SubmitFinalAnswer { player_id, answer })?;
+
+    fetch_final_round(engine)
 }
 
 #[command]
-pub fn finish_question_prematurely() -> Result<(), GameplayError> {
-    game().finish_question_prematurely().map_err(|e|{
-        log::error!("Operation failed: {:?}", e);
+pub fn judge_final_answer(
+    engine: State<'_, GameEngine>,
+    player_id: i32,
+    answered_correctly: bool,
+) -> Result<FinalRoundDto, GameplayError> {
+    let command = EngineCommand::JudgeFinalAnswer {
+        player_id: player_id as u8,
+        answered_correctly,
+    };
+    execute(&engine, command)?;
+
+    fetch_final_round(engine)
+}
+
+#[command]
+pub fn get_active_player_id(engine: State<'_, GameEngine>) -> i32 {
+    engine.context().get_active_player_id() as i32
+}
+
+/// Player whose turn is to select the next question. Zero if nobody chooses
+#[command]
+pub fn get_question_chooser_id(engine: State<'_, GameEngine>) -> i32 {
+    engine.context().get_question_chooser_id() as i32
+}
+
+#[command]
+pub fn is_allow_answer_required(engine: State<'_, GameEngine>) -> bool {
+    engine.context().current.question_type == QuestionType::Normal
+}
+
+#[command]
+pub fn fetch_round_stats(engine: State<'_, GameEngine>) -> RoundStatsDto {
+    map_round_stats_to_dto(&engine.context())
+}
+
+#[command]
+pub fn fetch_game_stats(engine: State<'_, GameEngine>) -> GameStatsDto {
+    map_game_stats_to_dto(&engine.context())
+}
+
+#[command]
+pub fn adjust_player_score(
+    engine: State<'_, GameEngine>,
+    player_id: i32,
+    adjustment_type: ScoreAdjustmentType,
+    amount: i32,
+    reason: String,
+) -> Result<Vec<PlayerGameDto>, GameplayError> {
+    let command = EngineCommand::AdjustScore {
+        player_id: player_id as u8,
+        adjustment_type,
+        amount,
+        reason,
+    };
+    execute(&engine, command)?;
+    Ok(map_players_to_player_game_dto(&engine.context().players))
+}
+
+/// Path to the journal file of the current game
+#[command]
+pub fn export_game_journal(engine: State<'_, GameEngine>) -> Option<String> {
+    engine
+        .context()
+        .journal
+        .file_path()
+        .map(|path| path.to_string_lossy().to_string())
+}
+
+/// Rebuilds the game from the journal file and returns its stats. Current game is not affected
+#[command]
+pub fn replay_game_journal(path: String) -> Result<GameStatsDto, GameJournalError> {
+    let replayed = replay_journal_file(Path::new(&path)).map_err(|e| {
+        log::error!("Can't replay journal {}: {:?}", path, e);
         e.current_context().clone()
-    })
+    })?;
+
+    Ok(map_game_stats_to_dto(&replayed))
 }
 
 #[command]
-pub fn init_next_round() {
-    game().init_next_round();
+pub fn fetch_game_state(engine: State<'_, GameEngine>) -> GameStateDto {
+    map_game_state_to_dto(&engine.context())
 }
 
 #[command]
-pub fn send_pip_victim(victim_id: i32) {
-    log::debug!("Victim id is: {}", victim_id);
+pub fn fetch_state_transitions(engine: State<'_, GameEngine>) -> Vec<StateTransitionDto> {
+    engine
+        .context()
+        .get_state_transitions()
+        .iter()
+        .map(map_state_transition_to_dto)
+        .collect()
+}
+
+#[command]
+pub fn fetch_round_timer(engine: State<'_, GameEngine>) -> RoundTimerDto {
+    map_round_timer_to_dto(&engine.context().current.round_timer)
 }
 
 #[command]
-pub fn get_active_player_id() -> i32 {
-    game().get_active_player_id() as i32
+pub fn set_round_timer_paused(
+    engine: State<'_, GameEngine>,
+    paused: bool,
+) -> Result<RoundTimerDto, GameplayError> {
+    execute(&engine, EngineCommand::SetRoundTimerPaused { paused })?;
+    Ok(map_round_timer_to_dto(&engine.context().current.round_timer))
 }
 
+/// Plays sudden-death questions for the players tied for first after the last round
 #[command]
-pub fn is_allow_answer_required() -> bool {
-    game().current.question_type == QuestionType::Normal
+pub fn start_tie_break(
+    engine: State<'_, GameEngine>,
+    source: TieBreakSource,
+) -> Result<TieBreakDto, GameplayError> {
+    execute(&engine, EngineCommand::StartTieBreak { source })?;
+    fetch_tie_break(engine).ok_or(GameplayError::InternalError)
 }
 
 #[command]
-pub fn fetch_round_stats() -> RoundStatsDto {
-    game().fetch_round_stats()
+pub fn fetch_tie_break(engine: State<'_, GameEngine>) -> Option<TieBreakDto> {
+    let game = engine.context();
+    game.get_tie_break()
+        .map(|tie_break| map_tie_break_to_dto(tie_break, game.get_current_round()))
+}
+
+/// Runs the host command in the engine, which persists the game and notifies the front ends
+fn execute(engine: &GameEngine, command: EngineCommand) -> Result<CommandOutput, GameplayError> {
+    let description = format!("{:?}", command);
+    engine
+        .execute(command)
+        .map(|response| response.output)
+        .map_err(|e| {
+            log::error!("{} failed: {:?}", description, e);
+            e.current_context().clone()
+        })
+}
+
+fn map_question_output_to_dto(output: CommandOutput) -> Result<QuestionDataDto, GameplayError> {
+    match output {
+        CommandOutput::Question {
+            theme,
+            question,
+            number,
+        } => Ok(map_question_to_question_dto(theme, *question, number)),
+        output => unexpected_output(output),
+    }
+}
+
+fn unexpected_output<T>(output: CommandOutput) -> Result<T, GameplayError> {
+    log::error!("Unexpected command output: {:?}", output);
+    Err(GameplayError::InternalError)
 }
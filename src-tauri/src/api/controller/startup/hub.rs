@@ -1,23 +1,24 @@
-use tauri::command;
+use tauri::{command, State};
 use crate::api::dto::PlayerSetupDto;
 use crate::api::mapper::{map_players_to_players_setup_dto};
 
-use crate::core::game_entities::{game, HubStatus};
+use crate::core::game_engine::GameEngine;
+use crate::core::game_entities::HubStatus;
 use crate::hub_comm::common::hub_api::HubType;
 use crate::hub_comm::hw::hw_hub_manager::HubManagerError;
 
 /// Set hub type to web or serial
 #[command]
-pub fn set_hub_type(hub_type: HubType) {
+pub fn set_hub_type(engine: State<'_, GameEngine>, hub_type: HubType) {
     log::debug!("Got request to set hub type: {:?}", hub_type);
-    let mut game = game();
+    let mut game = engine.context();
     game.select_hub_type(hub_type);
 }
 
 /// Tries to detect hub at given serial port. If successful saves port name
 #[command]
-pub fn discover_hub(path: String) -> Result<HubStatus, HubManagerError> {
-    let guard = game();
+pub fn discover_hub(engine: State<'_, GameEngine>, path: String) -> Result<HubStatus, HubManagerError> {
+    let guard = engine.context();
     let result = guard.get_locked_hub_mut().probe(&path);
     match result {
         Ok(status) => {
@@ -33,9 +34,9 @@ pub fn discover_hub(path: String) -> Result<HubStatus, HubManagerError> {
 
 /// Calls HUB to get all available players
 #[command]
-pub fn discover_players() -> Result<Vec<PlayerSetupDto>, HubManagerError> {
+pub fn discover_players(engine: State<'_, GameEngine>) -> Result<Vec<PlayerSetupDto>, HubManagerError> {
     log::info!("Discovering terminals");
-    let guard = game();
+    let guard = engine.context();
     let mut hub_guard = guard.get_locked_hub_mut();
 
     let players = hub_guard.discover_players().map_err(|e| {
@@ -1,9 +1,9 @@
 use std::sync::RwLockWriteGuard;
 
 use crate::api::dto::{HubRequestDto, HubResponseDto};
-use tauri::command;
+use tauri::{command, State};
 
-use crate::core::game_entities::game;
+use crate::core::game_engine::GameEngine;
 use crate::hub_comm::common::hub_api::HubManager;
 
 use crate::hub_comm::hw::hw_hub_manager::HubManagerError;
@@ -11,9 +11,9 @@ use crate::hub_comm::hw::internal::api_types::{HwHubIoError, HwHubRequest};
 
 /// Calls HUB to set specific radio channel
 #[command]
-pub fn set_hub_radio_channel(channel_id: i32) -> Result<(), HubManagerError> {
+pub fn set_hub_radio_channel(engine: State<'_, GameEngine>, channel_id: i32) -> Result<(), HubManagerError> {
     log::info!("Got channel id: {channel_id}");
-    let guard = game();
+    let guard = engine.context();
     let hub_guard = guard.get_locked_hub_mut();
 
     hub_guard
@@ -26,9 +26,9 @@ pub fn set_hub_radio_channel(channel_id: i32) -> Result<(), HubManagerError> {
 
 /// HUB Debug API
 #[command]
-pub fn setup_hub_connection(port_name: String) -> Result<(), HubManagerError> {
+pub fn setup_hub_connection(engine: State<'_, GameEngine>, port_name: String) -> Result<(), HubManagerError> {
     log::info!("Trying to open HUB connection");
-    let game_ctx = game();
+    let game_ctx = engine.context();
     let mut hub = game_ctx.get_locked_hub_mut();
     hub.setup_hub_connection(&port_name).map_err(|e| {
         log::error!("Operation failed: {:?}", e);
@@ -37,9 +37,9 @@ pub fn setup_hub_connection(port_name: String) -> Result<(), HubManagerError> {
 }
 
 #[command]
-pub fn send_raw_request_frame(request_frame: Vec<u8>) -> Result<Vec<u8>, HwHubIoError> {
+pub fn send_raw_request_frame(engine: State<'_, GameEngine>, request_frame: Vec<u8>) -> Result<Vec<u8>, HwHubIoError> {
     log::info!("Sending raw frame request to HUB");
-    let guard = game();
+    let guard = engine.context();
     let hub_guard = guard.get_locked_hub_mut();
     let Ok(handler) = hub_guard.hub_io_handler() else {
         return Err(HwHubIoError::NotInitializedError);
@@ -52,9 +52,9 @@ pub fn send_raw_request_frame(request_frame: Vec<u8>) -> Result<Vec<u8>, HwHubIo
 }
 
 #[command]
-pub fn send_hub_command(request: HubRequestDto) -> Result<HubResponseDto, HubManagerError> {
+pub fn send_hub_command(engine: State<'_, GameEngine>, request: HubRequestDto) -> Result<HubResponseDto, HubManagerError> {
     log::info!("Sending request to HUB.\n{:#?}", request);
-    let guard = game();
+    let guard = engine.context();
     let mut hub_guard = guard.get_locked_hub_mut();
 
     let request_enum = HwHubRequest::from_debug_request(request);
@@ -1,21 +1,28 @@
-use crate::api::dto::{ConfigDto, PackErrorData, PackInfoDto};
-use crate::api::mapper::{get_config_dto, map_package_to_pack_info_dto, update_players};
-use crate::core::game_entities::{game, GameplayError, Player, PlayerState};
-use tauri::command;
-
-use crate::api::dto::PlayerSetupDto;
-
-use crate::game_pack::game_pack_loader::{load_game_pack};
+use crate::api::dto::{ConfigDto, PackErrorData, PackInfoDto, PackValidationDto, ResumedGameDto};
+use crate::api::mapper::{get_config_dto, map_lint_report_to_dto, map_package_to_pack_info_dto, update_players};
+use crate::core::false_start::FalseStartPenalty;
+use crate::core::game_engine::{EngineCommand, GameEngine};
+use crate::core::game_entities::{GameplayError, Player, PlayerState};
+use crate::core::game_save::{read_game_save, GameSaveError};
+use crate::core::rule_set::RuleSetPreset;
+use error_stack::{Report, Result as ReportResult, ResultExt};
+use tauri::{command, State};
+
+use crate::api::dto::{PlayerSetupDto, TeamSetupDto};
+use crate::api::mapper::map_teams_to_team_setup_dto;
+use crate::core::team::{Team, TeamMember};
+
+use crate::game_pack::game_pack_loader::{load_game_pack, validate_game_pack, GamePackLoadingError};
 
 pub mod hub;
 pub mod hw_hub;
 
 /// Provide saved game configuration
 #[command]
-pub fn fetch_configuration() -> ConfigDto {
+pub fn fetch_configuration(engine: State<'_, GameEngine>) -> ConfigDto {
     log::info!("Fetching config");
 
-    let config = get_config_dto();
+    let config = get_config_dto(&engine.context());
     log::info!("Config: {:#?}", config);
 
     config
@@ -23,7 +30,7 @@ pub fn fetch_configuration() -> ConfigDto {
 
 /// Saves configuration to game context
 #[command]
-pub fn save_players(players: Vec<PlayerSetupDto>) {
+pub fn save_players(engine: State<'_, GameEngine>, players: Vec<PlayerSetupDto>) {
     log::debug!("Updating game context with new config: {players:#?}");
 
     let player_entities = players
@@ -40,59 +47,178 @@ pub fn save_players(players: Vec<PlayerSetupDto>) {
 
     log::info!("Converted players: {:#?}", player_entities);
 
-    update_players(&player_entities)
+    engine.setup(|game| update_players(game, &player_entities))
 }
 
-/// Load game pack into the game
+/// Sets up team play: terminals of each team play as one player
 #[command]
-pub fn get_pack_info(path: String) -> Result<PackInfoDto, PackErrorData> {
+pub fn save_teams(engine: State<'_, GameEngine>, teams: Vec<TeamSetupDto>) -> Result<(), GameplayError> {
+    log::debug!("Updating game context with teams: {teams:#?}");
+
+    let teams = teams
+        .into_iter()
+        .map(|team| Team {
+            id: team.teamId,
+            name: team.name,
+            icon: team.icon,
+            members: team
+                .members
+                .into_iter()
+                .filter(|m| m.isUsed)
+                .map(|m| TeamMember {
+                    term_id: m.termId,
+                    name: m.name,
+                    stats: Default::default(),
+                })
+                .collect(),
+        })
+        .collect();
+
+    engine.setup(|game| game.set_teams(teams)).map_err(|e| {
+        log::error!("Can't save teams: {:?}", e);
+        e.current_context().clone()
+    })
+}
+
+/// Provides the teams of the team play. Empty in individual play
+#[command]
+pub fn fetch_teams(engine: State<'_, GameEngine>) -> Vec<TeamSetupDto> {
+    map_teams_to_team_setup_dto(&engine.context().fetch_teams())
+}
+
+/// Load game pack into the game. Pack with warnings is loaded only if the host accepts them
+#[command]
+pub fn get_pack_info(
+    engine: State<'_, GameEngine>,
+    path: String,
+    accept_warnings: bool,
+) -> Result<PackInfoDto, PackErrorData> {
     log::info!("Obtained package path: {}", path);
 
-    let result = load_game_pack(path.as_str());
+    let result = load_game_pack(path.as_str(), accept_warnings);
 
     match result {
         Ok(pack) => {
-            game().game_pack = pack;
-
-            let pack_info_dto = map_package_to_pack_info_dto(&game().game_pack.content);
+            let pack_info_dto = engine.setup(|game| {
+                game.game_pack = pack;
+                map_package_to_pack_info_dto(&game.game_pack.content)
+            });
             log::info!("Pack info: {:#?}", pack_info_dto);
             Ok(pack_info_dto)
         }
-        Err(err) => {
-            log::error!("\n{err:?}");
-
-            let stack_trace = format!("{:?}", err);
-            let split = stack_trace
-                .split("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━")
-                .collect::<Vec<&str>>();
-            let &details = split.get(0).unwrap_or(&"");
-            let html_details = ansi_to_html::convert_escaped(details)
-                .unwrap_or_else(|e| {
-                log::error!("Can't map ASNI to HTML for {}\nError {}", details, e);
-                details.to_string()
-            });
+        Err(err) => Err(map_pack_error(path, err)),
+    }
+}
 
-            let data = PackErrorData {
-                path,
-                cause: err.current_context().to_string(),
-                details: html_details,
-            };
+/// Lists the problems of the pack so the host can decide whether to load it
+#[command]
+pub fn validate_pack(path: String) -> Result<PackValidationDto, PackErrorData> {
+    log::info!("Validating pack: {}", path);
 
-            Err(data)
-        }
+    match validate_game_pack(path.as_str()) {
+        Ok(report) => Ok(map_lint_report_to_dto(path, &report)),
+        Err(err) => Err(map_pack_error(path, err)),
+    }
+}
+
+fn map_pack_error(path: String, err: Report<GamePackLoadingError>) -> PackErrorData {
+    log::error!("\n{err:?}");
+
+    let stack_trace = format!("{:?}", err);
+    let split = stack_trace
+        .split("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━")
+        .collect::<Vec<&str>>();
+    let &details = split.get(0).unwrap_or(&"");
+    let html_details = ansi_to_html::convert_escaped(details).unwrap_or_else(|e| {
+        log::error!("Can't map ASNI to HTML for {}\nError {}", details, e);
+        details.to_string()
+    });
+
+    PackErrorData {
+        path,
+        cause: err.current_context().to_string(),
+        details: html_details,
     }
 }
 
 #[command]
-pub fn save_round_duration(round_minutes: i32) {
+pub fn save_round_duration(engine: State<'_, GameEngine>, round_minutes: i32) {
     log::info!("Round duration is {round_minutes}");
+    engine.context().set_round_duration(round_minutes);
+}
+
+/// Sets the time a player has to answer after the click. Zero disables the countdown
+#[command]
+pub fn save_answer_duration(engine: State<'_, GameEngine>, answer_seconds: i32) {
+    log::info!("Answer duration is {answer_seconds}");
+    engine.context().set_answer_window(answer_seconds);
+}
+
+/// Sets the penalty for clicking before answering is allowed
+#[command]
+pub fn save_false_start_penalty(engine: State<'_, GameEngine>, penalty: FalseStartPenalty) {
+    engine.context().set_false_start_penalty(penalty);
+}
+
+/// Selects the house rules the game is scored by. Allowed only before the game starts
+#[command]
+pub fn save_rule_set(engine: State<'_, GameEngine>, rule_set: RuleSetPreset) -> Result<(), GameplayError> {
+    engine.context().set_rule_set(rule_set).map_err(|e| {
+        log::error!("Can't save rule set: {:?}", e);
+        e.current_context().clone()
+    })
 }
 
 #[command]
-pub fn start_the_game() -> Result<(), GameplayError> {
+pub fn start_the_game(engine: State<'_, GameEngine>) -> Result<(), GameplayError> {
     log::info!("Triggered the game start");
-    game().start_the_game().map_err(|e| {
+    engine.execute(EngineCommand::StartGame).map_err(|e| {
         log::error!("{:#?}", e);
         e.current_context().clone()
+    })?;
+    Ok(())
+}
+
+/// Reloads the pack of the last saved game and restores the game state
+#[command]
+pub fn resume_last_game(engine: State<'_, GameEngine>) -> Result<ResumedGameDto, GameSaveError> {
+    log::info!("Resuming last game");
+    resume_game(&engine).map_err(|e| {
+        log::error!("Can't resume last game: {:?}", e);
+        e.current_context().clone()
+    })
+}
+
+fn resume_game(engine: &GameEngine) -> ReportResult<ResumedGameDto, GameSaveError> {
+    let data_dir = engine
+        .context()
+        .data_dir
+        .clone()
+        .ok_or(Report::new(GameSaveError::NoSavedGame))
+        .attach_printable("Game has no data dir")?;
+    let save = read_game_save(&data_dir)?;
+    let interrupted_question = save.current_question.clone();
+
+    // The host accepted the pack warnings when the game started
+    let pack = load_game_pack(&save.pack_path, true).map_err(|e| {
+        Report::new(GameSaveError::PackNotAvailable)
+            .attach_printable(format!("Can't load pack {}: {:?}", save.pack_path, e))
+    })?;
+
+    engine
+        .resume_game(save, pack)
+        .attach_printable("Can't restore saved game")?;
+
+    let game = engine.context();
+    let interrupted_question = interrupted_question.and_then(|q| {
+        let theme = game.game_pack.content.rounds.get(q.id.round)?.themes.get(q.id.theme)?;
+        let question = theme.questions.get(q.id.question)?;
+        Some((theme.name.clone(), question.price))
+    });
+    Ok(ResumedGameDto {
+        packInfo: map_package_to_pack_info_dto(&game.game_pack.content),
+        roundIndex: game.current.round_index,
+        interruptedQuestionTheme: interrupted_question.as_ref().map(|(theme, _)| theme.clone()),
+        interruptedQuestionPrice: interrupted_question.as_ref().map(|&(_, price)| price),
     })
 }
@@ -1,5 +1,10 @@
-use crate::core::game_entities::PlayerState;
-use crate::game_pack::pack_content_entities::QuestionMediaType;
+use crate::core::auction::AuctionBidType;
+use crate::core::final_round::FinalRoundStage;
+use crate::core::game_entities::{GameState, PlayerState};
+use crate::core::game_state_machine::GameCommand;
+use crate::core::score_adjustment::ScoreAdjustmentType;
+use crate::game_pack::pack_content_entities::{QuestionId, QuestionMediaType};
+use crate::game_pack::pack_lint::PackIssueSeverity;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize)]
@@ -9,6 +14,21 @@ pub struct PackErrorData {
     pub details: String,
 }
 
+#[derive(Debug, Serialize)]
+pub struct PackIssueDto {
+    pub severity: PackIssueSeverity,
+    pub location: String,
+    pub cause: String,
+}
+
+/// Every problem of the pack. Packs with warnings only can still be loaded
+#[derive(Debug, Serialize)]
+pub struct PackValidationDto {
+    pub path: String,
+    pub loadable: bool,
+    pub issues: Vec<PackIssueDto>,
+}
+
 ////////// Config ///////////
 #[derive(Debug, Serialize)]
 #[allow(non_snake_case)]
@@ -29,7 +49,16 @@ pub struct PlayerSetupDto {
     pub isUsed: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct TeamSetupDto {
+    pub teamId: u8,
+    pub icon: String,
+    pub name: String,
+    pub members: Vec<PlayerSetupDto>,
+}
+
+#[derive(Debug, Clone, Serialize)]
 #[allow(non_snake_case)]
 pub struct PlayerGameDto {
     pub id: i32,
@@ -59,7 +88,7 @@ pub struct PackInfoDto {
 }
 
 ////////// Round ///////////
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 #[allow(non_snake_case)]
 pub struct RoundDto {
     pub roundName: String,
@@ -67,28 +96,41 @@ pub struct RoundDto {
     pub roundTopics: Vec<TopicDto>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 #[allow(non_snake_case)]
 pub struct TopicDto {
     pub topicName: String,
     pub questions: Vec<QuestionDto>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 #[allow(non_snake_case)]
 pub struct QuestionDto {
     pub index: usize,
+    /// Addresses the question when the host selects it
+    pub id: QuestionId,
     pub price: i32,
 }
 
+#[derive(Debug, Serialize)]
+#[allow(non_snake_case)]
+pub struct ResumedGameDto {
+    pub packInfo: PackInfoDto,
+    pub roundIndex: usize,
+    pub interruptedQuestionTheme: Option<String>,
+    pub interruptedQuestionPrice: Option<i32>,
+}
+
 ////////// Round ///////////
-#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize)]
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[allow(non_snake_case)]
 pub enum QuestionType {
     #[default]
     Normal,
     PigInPoke,
     Auction,
+    /// Answered by the chooser alone, wrong answer costs nothing
+    Sponsored,
 }
 
 #[derive(Debug, Serialize)]
@@ -99,9 +141,21 @@ pub struct QuestionDataDto {
     pub price: i32,
     pub questionType: QuestionType,
     pub scenario: Vec<QuestionSceneDto>,
+    /// Shown once the question is closed
+    pub answerScenario: Vec<QuestionSceneDto>,
     pub answer: String,
 }
 
+/// Judging notes for the host screen only, never sent to the players
+#[derive(Debug, Serialize)]
+#[allow(non_snake_case)]
+pub struct HostNotesDto {
+    pub rightAnswers: Vec<String>,
+    pub wrongAnswers: Vec<String>,
+    pub comments: String,
+    pub sources: Vec<String>,
+}
+
 #[derive(Debug, Serialize)]
 #[allow(non_snake_case)]
 pub struct QuestionSceneDto {
@@ -109,6 +163,68 @@ pub struct QuestionSceneDto {
     pub content: String,
 }
 
+////////// Auction ///////////
+#[derive(Debug, Serialize)]
+#[allow(non_snake_case)]
+pub struct AuctionStateDto {
+    pub nominalPrice: i32,
+    pub highestBid: i32,
+    pub leaderId: Option<i32>,
+    pub currentBidderId: Option<i32>,
+    pub isFinished: bool,
+    pub bidders: Vec<AuctionBidderDto>,
+    pub history: Vec<AuctionBidDto>,
+}
+
+#[derive(Debug, Serialize)]
+#[allow(non_snake_case)]
+pub struct AuctionBidderDto {
+    pub id: i32,
+    pub playerName: String,
+    pub score: i32,
+    pub hasPassed: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[allow(non_snake_case)]
+pub struct AuctionBidDto {
+    pub playerId: i32,
+    pub bidType: AuctionBidType,
+    pub amount: i32,
+}
+
+////////// Final round ///////////
+#[derive(Debug, Serialize)]
+#[allow(non_snake_case)]
+pub struct FinalRoundDto {
+    pub stage: FinalRoundStage,
+    pub currentPlayerId: Option<i32>,
+    pub themes: Vec<FinalThemeDto>,
+    pub players: Vec<FinalPlayerDto>,
+}
+
+#[derive(Debug, Serialize)]
+#[allow(non_snake_case)]
+pub struct FinalThemeDto {
+    pub index: i32,
+    pub name: String,
+    pub isEliminated: bool,
+}
+
+/// Wager and answer stay hidden until the player's answer is judged
+#[derive(Debug, Serialize)]
+#[allow(non_snake_case)]
+pub struct FinalPlayerDto {
+    pub id: i32,
+    pub playerName: String,
+    pub score: i32,
+    pub hasWagered: bool,
+    pub hasAnswered: bool,
+    pub wager: Option<i32>,
+    pub answer: Option<String>,
+    pub answeredCorrectly: Option<bool>,
+}
+
 #[derive(Debug, Serialize)]
 #[allow(non_snake_case)]
 pub struct RoundStatsDto {
@@ -120,7 +236,70 @@ pub struct RoundStatsDto {
     pub totalWrongAnswers: i32,
     pub totalTries: i32,
     pub roundTime: String,
+    pub roundTimeLeft: String,
     pub players: Vec<PlayerStatsDto>,
+    pub teams: Vec<TeamStatsDto>,
+    pub scoreAdjustments: Vec<ScoreAdjustmentDto>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[allow(non_snake_case)]
+pub struct ActivePlayerDto {
+    /// Zero if nobody is active
+    pub playerId: i32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[allow(non_snake_case)]
+pub struct RoundProgressDto {
+    pub roundIndex: usize,
+    pub questionsLeft: i32,
+    pub hasNextQuestion: bool,
+    pub round: RoundDto,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[allow(non_snake_case)]
+pub struct BuzzerWinnerDto {
+    pub playerId: i32,
+    /// Time from answering allowed to the click
+    pub reactionMs: u32,
+}
+
+/// Answer judged wrong because the responder ran out of time
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[allow(non_snake_case)]
+pub struct AnswerTimeExpiredDto {
+    pub playerId: i32,
+    /// Whether other players may still answer the question
+    pub retry: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[allow(non_snake_case)]
+pub struct AnswerTimerDto {
+    pub playerId: i32,
+    pub windowMs: u64,
+    pub remainingMs: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[allow(non_snake_case)]
+pub struct RoundTimerDto {
+    pub elapsedSec: u64,
+    pub remainingSec: Option<u64>,
+    pub isPaused: bool,
+    pub isPausedByHost: bool,
+    pub isExpired: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[allow(non_snake_case)]
+pub struct TieBreakDto {
+    pub playerIds: Vec<i32>,
+    /// Set once a single leader is left
+    pub winnerId: Option<i32>,
+    pub questionsLeft: i32,
 }
 
 #[derive(Debug, Serialize)]
@@ -133,6 +312,81 @@ pub struct PlayerStatsDto {
     pub totalAnswers: i32,
     pub answeredCorrectly: i32,
     pub answeredWrong: i32,
+    pub falseStarts: i32,
+}
+
+#[derive(Debug, Serialize)]
+#[allow(non_snake_case)]
+pub struct TeamStatsDto {
+    pub team: PlayerStatsDto,
+    /// Answers each teammate gave on behalf of the team
+    pub members: Vec<PlayerStatsDto>,
+}
+
+#[derive(Debug, Serialize)]
+#[allow(non_snake_case)]
+pub struct GameStatsDto {
+    pub roundsPlayed: usize,
+    pub players: Vec<PlayerStatsDto>,
+    pub teams: Vec<TeamStatsDto>,
+    pub scoreAdjustments: Vec<ScoreAdjustmentDto>,
+}
+
+#[derive(Debug, Serialize)]
+#[allow(non_snake_case)]
+pub struct ScoreAdjustmentDto {
+    pub timestampMs: u64,
+    pub roundIndex: usize,
+    pub roundName: String,
+    pub questionTheme: Option<String>,
+    pub questionPrice: Option<i32>,
+    pub playerId: i32,
+    pub adjustmentType: ScoreAdjustmentType,
+    pub amount: i32,
+    pub scoreBefore: i32,
+    pub scoreAfter: i32,
+    pub reason: String,
+}
+
+////////// Game state ///////////
+#[derive(Debug, Clone, Serialize)]
+#[allow(non_snake_case)]
+pub struct GameStateDto {
+    pub state: GameState,
+    pub allowedCommands: Vec<GameCommand>,
+    /// Zero if nobody chooses the question
+    pub questionChooserId: i32,
+}
+
+////////// Scoreboard ///////////
+/// Read-only view of the game for the public scoreboard
+#[derive(Debug, Default, Clone, Serialize)]
+#[allow(non_snake_case)]
+pub struct ScoreboardDto {
+    pub players: Vec<PlayerGameDto>,
+    pub roundName: String,
+    /// Themes with the questions remaining on the board
+    pub roundTopics: Vec<TopicDto>,
+    pub questionsLeft: i32,
+    /// Zero if nobody is active
+    pub activePlayerId: i32,
+}
+
+#[derive(Debug, Serialize)]
+#[allow(non_snake_case)]
+pub struct StateTransitionDto {
+    pub timestampMs: u64,
+    pub from: GameState,
+    pub to: GameState,
+    pub command: GameCommand,
+}
+
+////////// History ///////////
+#[derive(Debug, Serialize)]
+#[allow(non_snake_case)]
+pub struct GameHistoryDto {
+    pub undoAction: Option<String>,
+    pub redoAction: Option<String>,
 }
 
 ////////// HUB DEBUG ///////////
@@ -0,0 +1,25 @@
+use tauri::{AppHandle, Manager};
+
+use crate::core::game_events::{GameEvent, GameEventSink};
+
+/// Pushes game events to all the app windows
+#[derive(Debug)]
+pub struct TauriEventSink {
+    app: AppHandle,
+}
+
+impl TauriEventSink {
+    pub fn new(app: AppHandle) -> Self {
+        Self { app }
+    }
+}
+
+impl GameEventSink for TauriEventSink {
+    fn emit(&self, event: &GameEvent) {
+        self.app
+            .emit_all(event.name(), event.clone())
+            .unwrap_or_else(|e| {
+                log::error!("Can't emit {} event: {:?}", event.name(), e);
+            });
+    }
+}
@@ -1,8 +1,26 @@
-use crate::api::dto::{ConfigDto, QuestionDataDto, QuestionSceneDto, RoundDto, TopicDto};
-use crate::api::dto::{PackInfoDto, PlayerGameDto, QuestionDto};
-use crate::core::game_entities::{game, Player};
-use crate::game_pack::pack_content_entities::{PackContent, Question, Round};
+use crate::api::dto::{
+    AnswerTimerDto, AuctionBidDto, AuctionBidderDto, AuctionStateDto, ConfigDto, FinalPlayerDto, FinalRoundDto,
+    FinalThemeDto, GameHistoryDto, GameStatsDto, HostNotesDto, PlayerStatsDto, QuestionDataDto, QuestionSceneDto, RoundDto, RoundStatsDto, RoundTimerDto,
+    ScoreAdjustmentDto, ScoreboardDto, StateTransitionDto, GameStateDto, TeamSetupDto, TeamStatsDto,
+    TieBreakDto, TopicDto,
+};
+use crate::api::dto::{PackInfoDto, PackIssueDto, PackValidationDto, PlayerGameDto, QuestionDto};
+use crate::core::answer_timer::AnswerTimer;
+use crate::core::auction::Auction;
+use crate::core::final_round::FinalRound;
+use crate::core::game_history::GameHistory;
+use crate::core::round_timer::RoundTimer;
+use crate::core::score_adjustment::ScoreAdjustment;
+use crate::core::game_entities::{GameContext, Player};
+use crate::core::game_state_machine::StateTransition;
+use crate::core::team::Team;
+use crate::core::tie_break::TieBreak;
+use crate::game_pack::pack_content_entities::{
+    Atom, HostNotes, PackContent, Question, QuestionId, Round,
+};
+use crate::game_pack::pack_lint::PackLintReport;
 use std::collections::HashMap;
+use std::time::Duration;
 
 
 use crate::hub_comm::hw::hw_hub_manager::discover_serial_ports;
@@ -10,8 +28,7 @@ use crate::hub_comm::hw::hw_hub_manager::discover_serial_ports;
 use super::dto::PlayerSetupDto;
 
 /// Takes whole game context and maps to config which contains only required elements
-pub fn get_config_dto() -> ConfigDto {
-    let context = game();
+pub fn get_config_dto(context: &GameContext) -> ConfigDto {
     let hub_guard = context.get_unlocked_hub();
     let players = context.players.values().cloned().collect();
     ConfigDto {
@@ -34,9 +51,8 @@ pub fn map_players_to_players_setup_dto(players: &Vec<Player>) -> Vec<PlayerSetu
 }
 
 /// Takes whole game context and maps to config which contains only required elements
-pub fn update_players(players: &Vec<Player>) {
-    let mut context = game();
-
+pub fn update_players(context: &mut GameContext, players: &Vec<Player>) {
+    context.teams.clear();
     context.players = players.iter().fold(HashMap::new(), |mut map, player| {
         map.insert(player.term_id, player.clone());
         map
@@ -58,14 +74,14 @@ pub fn map_package_to_pack_info_dto(package: &PackContent) -> PackInfoDto {
     let num_questions = package
         .rounds
         .iter()
-        .flat_map(|round| round.themes.iter())
-        .map(|(_, theme)| theme.questions.len())
+        .flat_map(|round| &round.themes)
+        .map(|theme| theme.questions.len())
         .sum::<usize>() as i32;
 
     let topic_list: Vec<String> = package
         .rounds
         .iter()
-        .flat_map(|round| round.themes.values().map(|theme| theme.name.clone()))
+        .flat_map(|round| round.themes.iter().map(|theme| theme.name.clone()))
         .collect();
 
     PackInfoDto {
@@ -95,6 +111,7 @@ pub fn map_players_to_player_game_dto(players: &HashMap<u8, Player>) -> Vec<Play
 ///
 /// # Arguments
 ///
+/// * `round_index` - Index of the round in the pack. Part of the question ids.
 /// * `round` - A reference to the `Round` struct to be converted.
 ///
 /// # Returns
@@ -104,42 +121,44 @@ pub fn map_players_to_player_game_dto(players: &HashMap<u8, Player>) -> Vec<Play
 /// # Examples
 ///
 /// ```
-/// use std::collections::HashMap;
 /// use serde::Serialize;
 /// use svoyak_tauri_app::api::mapper::map_round_to_dto;
-/// use svoyak_tauri_app::game_pack::pack_content_entities::{Round, RoundType, Theme};
-///
-/// // Assume proper implementations for RoundType and Theme structs.
+/// use svoyak_tauri_app::game_pack::pack_content_entities::Round;
 ///
 /// let round = Round {
 ///     name: "1".to_string(),
-///     round_type: RoundType::Normal,
-///     themes: HashMap::new(),
+///     round_type: "normal".to_string(),
+///     themes: vec![],
 ///     question_count: 30,
 ///     questions_left: 27,
 ///     normal_question_count: 29,
 ///     pip_question_count: 1
 /// };
 ///
-/// let round_dto = map_round_to_dto(&round);
+/// let round_dto = map_round_to_dto(0, &round);
 /// ```
-pub fn map_round_to_dto(round: &Round) -> RoundDto {
+pub fn map_round_to_dto(round_index: usize, round: &Round) -> RoundDto {
     let round_topics: Vec<TopicDto> = round
         .themes
-        .values()
-        .map(|theme| {
+        .iter()
+        .enumerate()
+        .map(|(theme_index, theme)| {
             log::info!("{theme:#?}");
-            let mut game_questions: Vec<Question> =
-                theme.questions.values().cloned().collect::<Vec<Question>>();
-            game_questions.sort_by(|q1, q2| q1.price.cmp(&q2.price));
-
             let mut questions = Vec::new();
-            game_questions.iter().enumerate().for_each(|(i, q)| {
-                questions.push(QuestionDto {
-                    index: i,
-                    price: q.price,
+            theme
+                .remaining_questions()
+                .enumerate()
+                .for_each(|(i, (question_index, q))| {
+                    questions.push(QuestionDto {
+                        index: i,
+                        id: QuestionId {
+                            round: round_index,
+                            theme: theme_index,
+                            question: question_index,
+                        },
+                        price: q.price,
+                    });
                 });
-            });
 
             TopicDto {
                 topicName: theme.name.clone(),
@@ -155,24 +174,338 @@ pub fn map_round_to_dto(round: &Round) -> RoundDto {
     }
 }
 
+pub fn map_lint_report_to_dto(path: String, report: &PackLintReport) -> PackValidationDto {
+    PackValidationDto {
+        path,
+        loadable: !report.has_errors(),
+        issues: report
+            .issues
+            .iter()
+            .map(|issue| PackIssueDto {
+                severity: issue.severity(),
+                location: issue.location.to_string(),
+                cause: issue.kind.to_string(),
+            })
+            .collect(),
+    }
+}
+
 pub fn map_question_to_question_dto(
     topic: String,
     question: Question,
     q_num: i32,
 ) -> QuestionDataDto {
+    let category = question
+        .pip_params
+        .as_ref()
+        .and_then(|p| p.theme.clone())
+        .unwrap_or(topic);
+
     QuestionDataDto {
         number: q_num,
-        category: topic,
-        price: question.price,
+        category,
+        price: question.play_price(),
         questionType: question.question_type,
-        scenario: question
-            .scenario
+        scenario: map_atoms_to_scene_dto(&question.scenario),
+        answerScenario: map_atoms_to_scene_dto(&question.answer_scenario),
+        answer: question.right_answer.clone(),
+    }
+}
+
+pub fn map_host_notes_to_dto(notes: &HostNotes) -> HostNotesDto {
+    HostNotesDto {
+        rightAnswers: notes.right_answers.clone(),
+        wrongAnswers: notes.wrong_answers.clone(),
+        comments: notes.comments.clone(),
+        sources: notes.sources.clone(),
+    }
+}
+
+pub fn map_atoms_to_scene_dto(atoms: &[Atom]) -> Vec<QuestionSceneDto> {
+    atoms
+        .iter()
+        .map(|a| QuestionSceneDto {
+            content: a.content.clone(),
+            mediaType: a.atom_type.clone(),
+        })
+        .collect()
+}
+
+pub fn map_auction_to_auction_state_dto(
+    auction: &Auction,
+    players: &HashMap<u8, Player>,
+) -> AuctionStateDto {
+    AuctionStateDto {
+        nominalPrice: auction.nominal_price,
+        highestBid: auction.highest_bid,
+        leaderId: auction.leader_id.map(|id| id as i32),
+        currentBidderId: auction.current_bidder().map(|id| id as i32),
+        isFinished: auction.is_finished(),
+        bidders: auction
+            .bidders
             .iter()
-            .map(|a| QuestionSceneDto {
-                content: a.content.clone(),
-                mediaType: a.atom_type.clone(),
+            .filter_map(|id| players.get(id))
+            .map(|p| AuctionBidderDto {
+                id: p.term_id as i32,
+                playerName: p.name.clone(),
+                score: p.stats.score,
+                hasPassed: auction.passed.contains(&p.term_id),
             })
             .collect(),
-        answer: question.right_answer.clone(),
+        history: auction
+            .history
+            .iter()
+            .map(|b| AuctionBidDto {
+                playerId: b.player_id as i32,
+                bidType: b.bid_type.clone(),
+                amount: b.amount,
+            })
+            .collect(),
+    }
+}
+
+pub fn map_final_round_to_dto(
+    final_round: &FinalRound,
+    players: &HashMap<u8, Player>,
+) -> FinalRoundDto {
+    FinalRoundDto {
+        stage: final_round.stage.clone(),
+        currentPlayerId: final_round.current_player_id().map(|id| id as i32),
+        themes: final_round
+            .themes
+            .iter()
+            .enumerate()
+            .map(|(index, t)| FinalThemeDto {
+                index: index as i32,
+                name: t.clone(),
+                isEliminated: final_round.eliminated_themes.contains(&index),
+            })
+            .collect(),
+        players: final_round
+            .participants
+            .iter()
+            .filter_map(|id| players.get(id))
+            .map(|p| {
+                let verdict = final_round.verdicts.get(&p.term_id).copied();
+                let revealed = verdict.is_some();
+                FinalPlayerDto {
+                    id: p.term_id as i32,
+                    playerName: p.name.clone(),
+                    score: p.stats.score,
+                    hasWagered: final_round.wagers.contains_key(&p.term_id),
+                    hasAnswered: final_round.answers.contains_key(&p.term_id),
+                    wager: final_round
+                        .wagers
+                        .get(&p.term_id)
+                        .copied()
+                        .filter(|_| revealed),
+                    answer: final_round
+                        .answers
+                        .get(&p.term_id)
+                        .cloned()
+                        .filter(|_| revealed),
+                    answeredCorrectly: verdict,
+                }
+            })
+            .collect(),
+    }
+}
+
+pub fn map_round_timer_to_dto(timer: &RoundTimer) -> RoundTimerDto {
+    RoundTimerDto {
+        elapsedSec: timer.elapsed().as_secs(),
+        remainingSec: timer.remaining().map(|r| r.as_secs()),
+        isPaused: timer.is_paused(),
+        isPausedByHost: timer.is_paused_by_host(),
+        isExpired: timer.is_expired(),
+    }
+}
+
+pub fn map_tie_break_to_dto(tie_break: &TieBreak, round: &Round) -> TieBreakDto {
+    TieBreakDto {
+        playerIds: tie_break.player_ids.iter().map(|&id| id as i32).collect(),
+        winnerId: tie_break.winner_id.map(|id| id as i32),
+        questionsLeft: round.questions_left,
+    }
+}
+
+pub fn map_answer_timer_to_dto(timer: &AnswerTimer) -> AnswerTimerDto {
+    AnswerTimerDto {
+        playerId: timer.player_id as i32,
+        windowMs: timer.window().as_millis() as u64,
+        remainingMs: timer.remaining().as_millis() as u64,
+    }
+}
+
+pub fn map_history_to_dto(history: &GameHistory) -> GameHistoryDto {
+    GameHistoryDto {
+        undoAction: history.next_undo().map(|a| a.description.clone()),
+        redoAction: history.next_redo().map(|a| a.description.clone()),
+    }
+}
+
+pub fn map_round_stats_to_dto(context: &GameContext) -> RoundStatsDto {
+    let round = context.get_current_round();
+    let timer = &context.current.round_timer;
+    RoundStatsDto {
+        roundName: round.name.to_owned(),
+        questionNumber: round.question_count,
+        normalQuestionNum: round.normal_question_count,
+        pigInPokeQuestionNum: round.pip_question_count,
+        totalCorrectAnswers: context.current.total_correct_answers,
+        totalWrongAnswers: context.current.total_wrong_answers,
+        totalTries: context.current.total_tries,
+        roundTime: format_duration(timer.elapsed()),
+        roundTimeLeft: timer
+            .remaining()
+            .map(format_duration)
+            .unwrap_or("Not limited".to_owned()),
+        players: map_players_to_player_stats_dto(&context.players),
+        teams: map_teams_to_team_stats_dto(&context.teams, &context.players),
+        scoreAdjustments: context
+            .score_adjustments
+            .iter()
+            .filter(|a| a.round_index == context.current.round_index)
+            .map(map_score_adjustment_to_dto)
+            .collect(),
+    }
+}
+
+pub fn map_game_stats_to_dto(context: &GameContext) -> GameStatsDto {
+    GameStatsDto {
+        roundsPlayed: context.current.round_index + 1,
+        players: map_players_to_player_stats_dto(&context.players),
+        teams: map_teams_to_team_stats_dto(&context.teams, &context.players),
+        scoreAdjustments: context
+            .score_adjustments
+            .iter()
+            .map(map_score_adjustment_to_dto)
+            .collect(),
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let total_seconds = duration.as_secs();
+    format!("{:02}:{:02}", total_seconds / 60, total_seconds % 60)
+}
+
+pub fn map_players_to_player_stats_dto(players: &HashMap<u8, Player>) -> Vec<PlayerStatsDto> {
+    players.values().map(map_player_to_player_stats_dto).collect()
+}
+
+fn map_player_to_player_stats_dto(p: &Player) -> PlayerStatsDto {
+    PlayerStatsDto {
+        id: p.term_id as i32,
+        name: p.name.to_owned(),
+        score: p.stats.score,
+        playerIconPath: p.icon.to_owned(),
+        totalAnswers: p.stats.total_tries,
+        answeredCorrectly: p.stats.correct_num,
+        answeredWrong: p.stats.wrong_num,
+        falseStarts: p.stats.false_start_num,
+    }
+}
+
+pub fn map_teams_to_team_stats_dto(
+    teams: &HashMap<u8, Team>,
+    players: &HashMap<u8, Player>,
+) -> Vec<TeamStatsDto> {
+    teams
+        .values()
+        .filter_map(|team| {
+            let Some(player) = players.get(&team.id) else {
+                log::error!("Team {} doesn't play the game", team.id);
+                return None;
+            };
+
+            let members = team
+                .members
+                .iter()
+                .map(|m| PlayerStatsDto {
+                    id: m.term_id as i32,
+                    name: m.name.to_owned(),
+                    score: m.stats.score,
+                    playerIconPath: team.icon.to_owned(),
+                    totalAnswers: m.stats.total_tries,
+                    answeredCorrectly: m.stats.correct_num,
+                    answeredWrong: m.stats.wrong_num,
+                    falseStarts: m.stats.false_start_num,
+                })
+                .collect();
+            Some(TeamStatsDto {
+                team: map_player_to_player_stats_dto(player),
+                members,
+            })
+        })
+        .collect()
+}
+
+pub fn map_teams_to_team_setup_dto(teams: &[Team]) -> Vec<TeamSetupDto> {
+    teams
+        .iter()
+        .map(|team| TeamSetupDto {
+            teamId: team.id,
+            icon: team.icon.clone(),
+            name: team.name.clone(),
+            members: team
+                .members
+                .iter()
+                .map(|m| PlayerSetupDto {
+                    icon: team.icon.clone(),
+                    isUsed: true,
+                    name: m.name.clone(),
+                    termId: m.term_id,
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+pub fn map_score_adjustment_to_dto(adjustment: &ScoreAdjustment) -> ScoreAdjustmentDto {
+    ScoreAdjustmentDto {
+        timestampMs: adjustment.timestamp_ms,
+        roundIndex: adjustment.round_index,
+        roundName: adjustment.round_name.clone(),
+        questionTheme: adjustment.question.as_ref().map(|(theme, _)| theme.clone()),
+        questionPrice: adjustment.question.as_ref().map(|(_, price)| *price),
+        playerId: adjustment.player_id as i32,
+        adjustmentType: adjustment.adjustment_type,
+        amount: adjustment.amount,
+        scoreBefore: adjustment.score_before,
+        scoreAfter: adjustment.score_after,
+        reason: adjustment.reason.clone(),
+    }
+}
+
+pub fn map_game_state_to_dto(context: &GameContext) -> GameStateDto {
+    GameStateDto {
+        state: context.current.game_state().clone(),
+        allowedCommands: context.allowed_commands(),
+        questionChooserId: context.get_question_chooser_id() as i32,
+    }
+}
+
+pub fn map_game_to_scoreboard_dto(context: &GameContext) -> ScoreboardDto {
+    let mut players = map_players_to_player_game_dto(&context.players);
+    players.sort_by_key(|p| p.id);
+    let round = context.game_pack.content.rounds.get(context.current.round_index);
+
+    ScoreboardDto {
+        players,
+        roundName: round.map(|r| r.name.clone()).unwrap_or_default(),
+        roundTopics: round
+            .map(|r| map_round_to_dto(context.current.round_index, r).roundTopics)
+            .unwrap_or_default(),
+        questionsLeft: round.map(|r| r.questions_left).unwrap_or_default(),
+        activePlayerId: context.current.active_player_id() as i32,
+    }
+}
+
+pub fn map_state_transition_to_dto(transition: &StateTransition) -> StateTransitionDto {
+    StateTransitionDto {
+        timestampMs: transition.timestamp_ms,
+        from: transition.from.clone(),
+        to: transition.to.clone(),
+        command: transition.command,
     }
 }
@@ -0,0 +1,238 @@
+use error_stack::{Report, Result, ResultExt};
+use rgb::RGB8;
+use std::thread;
+use std::thread::{sleep, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::api::dto::AnswerTimeExpiredDto;
+use crate::core::game_engine::WeakGameEngine;
+use crate::core::game_entities::{GameContext, GameplayError};
+use crate::core::game_events::GameEvent;
+
+const WATCHDOG_TICK_MS: u64 = 100;
+pub(crate) const LIGHT_OFF: RGB8 = RGB8::new(0, 0, 0);
+const LIGHT_PLENTY_OF_TIME: RGB8 = RGB8::new(0, 255, 0);
+const LIGHT_HURRY_UP: RGB8 = RGB8::new(255, 255, 0);
+const LIGHT_TIME_IS_RUNNING_OUT: RGB8 = RGB8::new(255, 0, 0);
+
+/// Countdown for the player who has the right to answer
+#[derive(Debug, Clone)]
+pub struct AnswerTimer {
+    /// Identifies the answer attempt the timer was started for
+    pub attempt: u32,
+    pub player_id: u8,
+    window: Duration,
+    deadline: Instant,
+}
+
+impl AnswerTimer {
+    pub fn start(attempt: u32, player_id: u8, window: Duration) -> Self {
+        Self {
+            attempt,
+            player_id,
+            window,
+            deadline: Instant::now() + window,
+        }
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.deadline.saturating_duration_since(Instant::now())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    pub fn extend(&mut self, extra_time: Duration) {
+        self.deadline += extra_time;
+        self.window += extra_time;
+    }
+
+    /// Terminal light color showing how much time is left
+    pub fn light_color(&self) -> RGB8 {
+        let remaining = self.remaining().as_secs_f32();
+        let window = self.window.as_secs_f32().max(f32::EPSILON);
+        match remaining / window {
+            r if r > 0.5 => LIGHT_PLENTY_OF_TIME,
+            r if r > 0.2 => LIGHT_HURRY_UP,
+            r if r > 0.0 => LIGHT_TIME_IS_RUNNING_OUT,
+            _ => LIGHT_OFF,
+        }
+    }
+}
+
+impl GameContext {
+    /// Starts the countdown for the responder. Does nothing when the answer window is disabled
+    pub fn start_answer_timer(&mut self, player_id: u8) {
+        let Some(window) = self.answer_window else {
+            return;
+        };
+
+        self.current.answer_attempt += 1;
+        let timer = AnswerTimer::start(self.current.answer_attempt, player_id, window);
+        log::info!("Player {} has {:?} to answer", player_id, window);
+        self.set_term_light(player_id, timer.light_color());
+        self.current.answer_timer = Some(timer);
+    }
+
+    pub fn extend_answer_timer(&mut self, extra_time: Duration) -> Result<(), GameplayError> {
+        let timer = self
+            .current
+            .answer_timer
+            .as_mut()
+            .ok_or(Report::new(GameplayError::OperationForbidden))
+            .attach_printable("No answer countdown is running")?;
+
+        timer.extend(extra_time);
+        log::info!(
+            "Answer time extended by {:?}. Left: {:?}",
+            extra_time,
+            timer.remaining()
+        );
+        Ok(())
+    }
+
+    pub fn cancel_answer_timer(&mut self) {
+        let Some(timer) = self.current.answer_timer.take() else {
+            return;
+        };
+
+        log::info!("Answer countdown for player {} is stopped", timer.player_id);
+        self.set_term_light(timer.player_id, LIGHT_OFF);
+    }
+
+    /// Judges the answer as wrong if the countdown of the given attempt ran out.
+    /// Returns the judged answer for the front ends
+    pub fn expire_answer_timer(
+        &mut self,
+        attempt: u32,
+    ) -> Result<Option<AnswerTimeExpiredDto>, GameplayError> {
+        let Some(player_id) = self
+            .current
+            .answer_timer
+            .as_ref()
+            .filter(|t| t.attempt == attempt && t.is_expired())
+            .map(|t| t.player_id)
+        else {
+            return Ok(None);
+        };
+
+        log::info!("Answer time is over. Answer is considered wrong");
+        let retry = self.answer_question(false)?;
+        Ok(Some(AnswerTimeExpiredDto {
+            playerId: player_id as i32,
+            retry,
+        }))
+    }
+
+    /// Lights the terminals of the player. In team play all teammates are lit
+    pub(crate) fn set_term_light(&self, player_id: u8, color: RGB8) {
+        let hub = self.get_unlocked_hub();
+        for term_id in self.terminals_of_player(player_id) {
+            hub.set_term_light_color(term_id, color)
+                .unwrap_or_else(|error| {
+                    log::error!("Can't set terminal {} light. Err {:?}", term_id, error);
+                });
+        }
+    }
+}
+
+/// Watches the running answer countdown: mirrors it to the responder terminal light and
+/// judges the answer as wrong once the time is over
+pub(crate) fn start_answer_timer_watchdog(engine: WeakGameEngine, attempt: u32) -> JoinHandle<()> {
+    log::debug!("Starting answer countdown watchdog for attempt {}", attempt);
+
+    thread::spawn(move || {
+        let mut last_color = None;
+        loop {
+            sleep(Duration::from_millis(WATCHDOG_TICK_MS));
+            let Some(engine) = engine.upgrade() else {
+                break;
+            };
+            let mut game = engine.context();
+
+            let Some(timer) = game.current.answer_timer.clone() else {
+                break;
+            };
+            if timer.attempt != attempt {
+                break;
+            }
+
+            if timer.is_expired() {
+                match game.expire_answer_timer(attempt) {
+                    Ok(Some(expired)) => game.emit_event(GameEvent::AnswerTimeExpired(expired)),
+                    Ok(None) => {}
+                    Err(e) => log::error!("Can't judge expired answer: {:?}", e),
+                }
+                engine.commit(&mut game);
+                break;
+            }
+
+            let color = timer.light_color();
+            if last_color != Some(color) {
+                game.set_term_light(timer.player_id, color);
+                last_color = Some(color);
+            }
+        }
+        log::debug!("Answer countdown watchdog for attempt {} is done", attempt);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::dto::QuestionType;
+    use crate::core::game_entities::{GameState, Player};
+    use crate::game_pack::pack_content_entities::test_pack::{make_pack, make_round, make_theme};
+    use crate::game_pack::pack_content_entities::QuestionId;
+
+    #[test]
+    fn test_answer_timer_extension() {
+        let mut timer = AnswerTimer::start(1, 1, Duration::from_millis(50));
+        assert_eq!(timer.light_color(), LIGHT_PLENTY_OF_TIME);
+
+        sleep(Duration::from_millis(60));
+        assert!(timer.is_expired());
+        assert_eq!(timer.light_color(), LIGHT_OFF);
+
+        timer.extend(Duration::from_secs(5));
+        assert!(!timer.is_expired());
+        assert_eq!(timer.window(), Duration::from_millis(5050));
+    }
+
+    #[test]
+    fn test_pig_in_poke_victim_gets_countdown() {
+        let mut theme = make_theme("Music", &[100]);
+        theme.questions[0].question_type = QuestionType::PigInPoke;
+        let mut ctx = GameContext::default();
+        ctx.game_pack = make_pack(vec![make_round(vec![theme])]);
+        ctx.players.insert(1, Player::new(1));
+        ctx.players.insert(2, Player::new(2));
+        ctx.set_answer_window(1);
+        ctx.begin_game().expect("Test");
+        ctx.assign_first_chooser(1).expect("Test");
+        ctx.get_pack_question(QuestionId::default()).expect("Test");
+        assert!(ctx.current.answer_timer.is_none());
+
+        ctx.set_pip_victim(2).expect("Test");
+        let timer = ctx.current.answer_timer.clone().expect("Test");
+        assert_eq!(timer.player_id, 2);
+        assert_eq!(ctx.expire_answer_timer(timer.attempt).expect("Test"), None);
+
+        ctx.current.answer_timer = Some(AnswerTimer::start(timer.attempt, 2, Duration::ZERO));
+        let expired = ctx.expire_answer_timer(timer.attempt).expect("Test");
+        assert_eq!(
+            expired,
+            Some(AnswerTimeExpiredDto {
+                playerId: 2,
+                retry: false,
+            })
+        );
+        assert_eq!(ctx.players[&2].stats.score, -100);
+        assert_eq!(*ctx.current.game_state(), GameState::QuestionChoosing);
+    }
+}
@@ -0,0 +1,305 @@
+use error_stack::{Report, Result, ResultExt};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+
+use crate::api::dto::QuestionType;
+use crate::core::game_entities::{GameContext, GameState, GameplayError, PlayerState};
+use crate::core::game_journal::JournalEvent;
+use crate::core::game_state_machine::GameCommand;
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum AuctionBidType {
+    Bid,
+    AllIn,
+    Pass,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AuctionBid {
+    pub player_id: u8,
+    pub bid_type: AuctionBidType,
+    pub amount: i32,
+}
+
+/// Bidding for the auction question.
+///
+/// Players bid in turn starting from the question chooser. A bid has to be at least the
+/// nominal price, higher than the current highest bid and not higher than the player's score.
+/// All-in bids the whole score. The auction is over when everybody except the leader has
+/// passed. If all players pass without a bid, the chooser plays the question for the nominal
+/// price.
+#[derive(Debug, Clone, Default)]
+pub struct Auction {
+    pub nominal_price: i32,
+    pub bidders: Vec<u8>,
+    pub passed: Vec<u8>,
+    pub highest_bid: i32,
+    pub leader_id: Option<u8>,
+    pub history: Vec<AuctionBid>,
+    turn: usize,
+}
+
+impl Auction {
+    pub fn new(nominal_price: i32, bidders: Vec<u8>) -> Self {
+        Self {
+            nominal_price,
+            bidders,
+            ..Default::default()
+        }
+    }
+
+    pub fn current_bidder(&self) -> Option<u8> {
+        if self.is_finished() {
+            return None;
+        }
+        self.bidders.get(self.turn).copied()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        let active: Vec<u8> = self.active_bidders();
+        match self.leader_id {
+            Some(leader_id) => active.iter().all(|&id| id == leader_id),
+            None => active.is_empty(),
+        }
+    }
+
+    /// Returns the winner and the winning bid once the auction is finished
+    pub fn winner(&self) -> Option<(u8, i32)> {
+        if !self.is_finished() {
+            return None;
+        }
+
+        match self.leader_id {
+            Some(leader_id) => Some((leader_id, self.highest_bid)),
+            None => self
+                .bidders
+                .first()
+                .map(|&chooser_id| (chooser_id, self.nominal_price)),
+        }
+    }
+
+    pub fn place_bid(
+        &mut self,
+        player_id: u8,
+        player_score: i32,
+        bid_type: AuctionBidType,
+        amount: i32,
+    ) -> Result<(), GameplayError> {
+        let Some(current_bidder) = self.current_bidder() else {
+            return Err(Report::new(GameplayError::BidNotAllowed))
+                .attach_printable("Auction is already finished");
+        };
+
+        if current_bidder != player_id {
+            return Err(Report::new(GameplayError::BidNotAllowed)).attach_printable(format!(
+                "It's turn of player {} to bid, not {}",
+                current_bidder, player_id
+            ));
+        }
+
+        let amount = match bid_type {
+            AuctionBidType::Pass => 0,
+            AuctionBidType::AllIn => {
+                if player_score <= 0 || player_score <= self.highest_bid {
+                    return Err(Report::new(GameplayError::BidNotAllowed)).attach_printable(
+                        format!(
+                            "Player {} can't go all-in with score {}. Highest bid: {}",
+                            player_id, player_score, self.highest_bid
+                        ),
+                    );
+                }
+                player_score
+            }
+            AuctionBidType::Bid => {
+                if amount < self.nominal_price
+                    || amount <= self.highest_bid
+                    || amount > player_score
+                {
+                    return Err(Report::new(GameplayError::BidNotAllowed)).attach_printable(
+                        format!(
+                            "Invalid bid {} from player {}. Nominal price: {}, highest bid: {}, score: {}",
+                            amount, player_id, self.nominal_price, self.highest_bid, player_score
+                        ),
+                    );
+                }
+                amount
+            }
+        };
+
+        log::info!("Player {} bids {:?}: {}", player_id, bid_type, amount);
+        if bid_type == AuctionBidType::Pass {
+            self.passed.push(player_id);
+        } else {
+            self.highest_bid = amount;
+            self.leader_id = Some(player_id);
+        }
+
+        self.history.push(AuctionBid {
+            player_id,
+            bid_type,
+            amount,
+        });
+        self.advance_turn();
+        Ok(())
+    }
+
+    fn active_bidders(&self) -> Vec<u8> {
+        self.bidders
+            .iter()
+            .filter(|&id| !self.passed.contains(id))
+            .copied()
+            .collect()
+    }
+
+    fn advance_turn(&mut self) {
+        for offset in 1..=self.bidders.len() {
+            let index = (self.turn + offset) % self.bidders.len();
+            let id = self.bidders[index];
+            if !self.passed.contains(&id) && Some(id) != self.leader_id {
+                self.turn = index;
+                return;
+            }
+        }
+    }
+}
+
+impl GameContext {
+    pub fn start_auction(&mut self, chooser_id: u8) {
+        let mut bidders: Vec<u8> = self
+            .players
+            .values()
+            .filter(|&p| p.state != PlayerState::Dead)
+            .map(|p| p.term_id)
+            .collect();
+        bidders.sort();
+
+        if let Some(chooser_pos) = bidders.iter().position(|&id| id == chooser_id) {
+            bidders.rotate_left(chooser_pos);
+        }
+
+        log::info!("Starting auction. Bidders order: {:?}", bidders);
+        self.current.auction = Some(Auction::new(self.current.question_nominal_price, bidders));
+    }
+
+    pub fn place_auction_bid(
+        &mut self,
+        player_id: u8,
+        bid_type: AuctionBidType,
+        amount: i32,
+    ) -> Result<(), GameplayError> {
+        self.ensure_command_allowed(GameCommand::PlaceAuctionBid)?;
+        if self.current.question_type != QuestionType::Auction {
+            return Err(Report::new(GameplayError::OperationForbidden)).attach_printable(format!(
+                "Can't bid on {:?} question",
+                self.current.question_type
+            ));
+        }
+
+        let player_score = self
+            .players
+            .get(&player_id)
+            .ok_or(Report::new(GameplayError::PlayerNotPresent))
+            .attach_printable(format!("Can't find player with id {}", player_id))?
+            .stats
+            .score;
+
+        let auction = self
+            .current
+            .auction
+            .as_mut()
+            .ok_or(Report::new(GameplayError::OperationForbidden))
+            .attach_printable("Auction is not started")?;
+
+        auction.place_bid(player_id, player_score, bid_type.clone(), amount)?;
+
+        if let Some((winner_id, winning_bid)) = auction.winner() {
+            log::info!("Auction won by player {} with bid {}", winner_id, winning_bid);
+            self.current.question_price = winning_bid;
+            self.assign_question_to_player(winner_id, GameCommand::PlaceAuctionBid)?;
+        }
+        self.journal.record(JournalEvent::AuctionBidPlaced {
+            player_id,
+            bid_type,
+            amount,
+        });
+        Ok(())
+    }
+
+    pub fn get_auction(&self) -> Result<&Auction, GameplayError> {
+        self.current
+            .auction
+            .as_ref()
+            .ok_or(Report::new(GameplayError::OperationForbidden))
+            .attach_printable("Auction is not started")
+    }
+
+    /// Makes the player the only one who may answer the current question. No buzzer race.
+    /// The answer countdown starts right away
+    pub(crate) fn assign_question_to_player(
+        &mut self,
+        player_id: u8,
+        command: GameCommand,
+    ) -> Result<(), GameplayError> {
+        self.players.values_mut().for_each(|p| {
+            if p.term_id == player_id {
+                p.state = PlayerState::Target;
+            } else if p.state != PlayerState::Dead {
+                p.state = PlayerState::Inactive;
+            }
+        });
+
+        self.allow_answer_timestamp.swap(u32::MAX, Ordering::Relaxed);
+        self.current.set_active_player_id(player_id);
+        self.current.click_for_answer_allowed = false;
+        self.current.answer_allowed = true;
+        self.update_game_state(GameState::AnswerRequested, command)?;
+        self.start_answer_timer(player_id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auction_won_by_highest_bid() {
+        let mut auction = Auction::new(200, vec![2, 3, 1]);
+
+        auction.place_bid(2, 500, AuctionBidType::Bid, 200).expect("Test");
+        auction.place_bid(3, 1000, AuctionBidType::Bid, 400).expect("Test");
+        auction.place_bid(1, 300, AuctionBidType::Pass, 0).expect("Test");
+        assert_eq!(auction.current_bidder(), Some(2));
+        auction.place_bid(2, 500, AuctionBidType::AllIn, 0).expect("Test");
+        assert_eq!(auction.current_bidder(), Some(3));
+        auction.place_bid(3, 1000, AuctionBidType::Pass, 0).expect("Test");
+
+        assert!(auction.is_finished());
+        assert_eq!(auction.winner(), Some((2, 500)));
+        assert_eq!(auction.history.len(), 5);
+    }
+
+    #[test]
+    fn test_auction_rejects_invalid_bids() {
+        let mut auction = Auction::new(200, vec![1, 2]);
+
+        assert!(auction.place_bid(2, 500, AuctionBidType::Bid, 300).is_err());
+        assert!(auction.place_bid(1, 500, AuctionBidType::Bid, 100).is_err());
+        assert!(auction.place_bid(1, 500, AuctionBidType::Bid, 600).is_err());
+        auction.place_bid(1, 500, AuctionBidType::Bid, 300).expect("Test");
+        assert!(auction.place_bid(2, 300, AuctionBidType::AllIn, 0).is_err());
+        assert!(auction.place_bid(2, 900, AuctionBidType::Bid, 300).is_err());
+    }
+
+    #[test]
+    fn test_auction_goes_to_chooser_when_everybody_passes() {
+        let mut auction = Auction::new(200, vec![3, 1, 2]);
+
+        auction.place_bid(3, 0, AuctionBidType::Pass, 0).expect("Test");
+        auction.place_bid(1, 0, AuctionBidType::Pass, 0).expect("Test");
+        assert!(!auction.is_finished());
+        auction.place_bid(2, 0, AuctionBidType::Pass, 0).expect("Test");
+
+        assert_eq!(auction.winner(), Some((3, 200)));
+    }
+}
@@ -0,0 +1,82 @@
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::api::dto::BuzzerWinnerDto;
+use crate::core::game_engine::WeakGameEngine;
+use crate::core::game_entities::{GameContext, GameState};
+use crate::core::game_journal::JournalEvent;
+use crate::hub_comm::hw::hw_hub_manager::get_epoch_ms;
+use crate::hub_comm::hw::internal::api_types::TermEvent;
+
+/// How often lockouts are checked when no clicks come
+const ARBITER_TICK_MS: u64 = 100;
+
+impl GameContext {
+    /// Takes the clicks received from the hub. Once answering is allowed, the fastest click
+    /// gets the right to answer. Returns the winner
+    pub fn arbitrate_clicks(&mut self, events: Vec<TermEvent>) -> Option<BuzzerWinnerDto> {
+        events.iter().for_each(|e| {
+            self.journal
+                .record(JournalEvent::TermEventReceived { event: e.clone() });
+        });
+        if let Ok(now) = get_epoch_ms() {
+            self.release_expired_lockouts(now);
+        }
+
+        let presses = events.clone();
+        let events = self.assign_clicks_to_teams(events);
+        let base_timestamp = self.allow_answer_timestamp.load(Ordering::Relaxed);
+        let mut events = self.filter_false_starts(events, &presses, base_timestamp);
+        if *self.current.game_state() != GameState::AnswerAllowed {
+            if !events.is_empty() {
+                log::debug!("Answering is not allowed. Skipping: {:?}", events);
+            }
+            return None;
+        }
+
+        events.sort_by_key(|e| e.timestamp);
+        let event = self.find_the_fastest_event(&events)?.clone();
+        self.current.answering_term_id = self.find_team_member_click(&presses, &event);
+        if let Err(e) = self.accept_fastest_click(event.term_id) {
+            log::error!("Can't give the answer to player {}: {:?}", event.term_id, e);
+            return None;
+        }
+
+        Some(BuzzerWinnerDto {
+            playerId: event.term_id as i32,
+            reactionMs: event.timestamp.saturating_sub(base_timestamp),
+        })
+    }
+}
+
+/// Consumes the hub clicks in the background, so commands don't wait for the players to click.
+/// Stops once the next game starts or the engine is dropped
+pub(crate) fn spawn_buzzer_arbiter(
+    engine: WeakGameEngine,
+    session: u32,
+    receiver: Receiver<TermEvent>,
+) -> JoinHandle<()> {
+    log::info!("Starting buzzer arbiter #{}", session);
+
+    thread::spawn(move || {
+        loop {
+            let events = match receiver.recv_timeout(Duration::from_millis(ARBITER_TICK_MS)) {
+                Ok(event) => std::iter::once(event).chain(receiver.try_iter()).collect(),
+                Err(RecvTimeoutError::Timeout) => vec![],
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+
+            let Some(engine) = engine.upgrade() else {
+                break;
+            };
+            if engine.context().buzzer_session != session {
+                break;
+            }
+            engine.process_clicks(events);
+        }
+        log::info!("Buzzer arbiter #{} is stopped", session);
+    })
+}
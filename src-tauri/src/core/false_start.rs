@@ -0,0 +1,225 @@
+use rgb::RGB8;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::core::answer_timer::LIGHT_OFF;
+use crate::core::game_entities::GameContext;
+use crate::core::game_journal::JournalEvent;
+use crate::hub_comm::hw::hw_hub_manager::get_epoch_ms;
+use crate::hub_comm::hw::internal::api_types::TermButtonState::Pressed;
+use crate::hub_comm::hw::internal::api_types::TermEvent;
+
+const LIGHT_LOCKED_OUT: RGB8 = RGB8::new(255, 0, 255);
+
+/// Penalty for the player who clicks before answering is allowed
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum FalseStartPenalty {
+    /// Early clicks are only counted
+    #[default]
+    Disabled,
+    /// Player can't click for the given number of milliseconds after the early click
+    Lockout(u32),
+    /// Player can't click until the question is over
+    WholeQuestion,
+}
+
+/// Where a click falls relative to the answer window of the current question
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ClickKind {
+    /// Click left from before the question. Ignored
+    Stale,
+    FalseStart,
+    LockedOut,
+    InTime,
+}
+
+/// Early clicks of the question being played
+#[derive(Debug, Default, Clone)]
+pub struct FalseStarts {
+    /// Hub timestamp since which early clicks are counted as false starts.
+    /// Clicks aren't counted while no question is played
+    window_start: Option<u32>,
+    /// Hub timestamp until which each locked out player is ignored
+    locked_until: HashMap<u8, u32>,
+}
+
+impl FalseStarts {
+    pub fn is_locked_out(&self, player_id: u8, timestamp: u32) -> bool {
+        self.locked_until
+            .get(&player_id)
+            .map(|&until| timestamp < until)
+            .unwrap_or(false)
+    }
+
+    pub fn locked_out_players(&self) -> Vec<u8> {
+        self.locked_until.keys().copied().collect()
+    }
+}
+
+impl GameContext {
+    pub fn set_false_start_penalty(&mut self, penalty: FalseStartPenalty) {
+        log::info!("False start penalty set to: {:?}", penalty);
+        self.false_start_penalty = penalty;
+    }
+
+    /// Starts counting early clicks. Clicks made before are left from the previous answer.
+    /// The window is closed once the question is over
+    pub(crate) fn open_false_start_window(&mut self) {
+        let timestamp = get_epoch_ms().unwrap_or_else(|e| {
+            log::error!("Can't get false start window timestamp: {:?}", e);
+            u32::default()
+        });
+        self.current.false_starts.window_start = Some(timestamp);
+    }
+
+    pub(crate) fn classify_click(&self, event: &TermEvent, base_timestamp: u32) -> ClickKind {
+        let false_starts = &self.current.false_starts;
+        let Some(window_start) = false_starts.window_start else {
+            return ClickKind::Stale;
+        };
+
+        if event.timestamp < window_start {
+            ClickKind::Stale
+        } else if event.timestamp < base_timestamp {
+            ClickKind::FalseStart
+        } else if false_starts.is_locked_out(event.term_id, event.timestamp) {
+            ClickKind::LockedOut
+        } else {
+            ClickKind::InTime
+        }
+    }
+
+    /// Counts the early click and locks the player out according to the penalty.
+    /// In team play the click is counted for the teammate who made it as well
+    pub(crate) fn register_false_start(
+        &mut self,
+        player_id: u8,
+        term_id: Option<u8>,
+        timestamp: u32,
+    ) {
+        let Some(player) = self.players.get_mut(&player_id) else {
+            log::debug!("Unknown terminal id {} false start. Skipping", player_id);
+            return;
+        };
+        player.stats.false_start_num += 1;
+        if let Some(term_id) = term_id {
+            self.record_member_false_start(player_id, term_id);
+        }
+        self.journal.record(JournalEvent::FalseStart {
+            player_id,
+            term_id,
+            timestamp,
+        });
+
+        let locked_until = match self.false_start_penalty {
+            FalseStartPenalty::Disabled => return,
+            FalseStartPenalty::Lockout(duration_ms) => timestamp.saturating_add(duration_ms),
+            FalseStartPenalty::WholeQuestion => u32::MAX,
+        };
+        log::info!(
+            "Player {} false start. Locked out until {}",
+            player_id,
+            locked_until
+        );
+        self.current
+            .false_starts
+            .locked_until
+            .insert(player_id, locked_until);
+    }
+
+    /// Sorts out early and locked out clicks, leaving the ones which compete for the answer.
+    /// Presses are the raw hub clicks, used to find the teammate who made the early click
+    pub(crate) fn filter_false_starts(
+        &mut self,
+        events: Vec<TermEvent>,
+        presses: &[TermEvent],
+        base_timestamp: u32,
+    ) -> Vec<TermEvent> {
+        let mut in_time_events = vec![];
+        for e in events {
+            match self.classify_click(&e, base_timestamp) {
+                ClickKind::Stale => log::debug!("Click before the question. Skipping: {:?}", e),
+                ClickKind::FalseStart if e.state == Pressed => {
+                    log::info!("Answer too early. Event {:?}", e);
+                    let term_id = self.find_team_member_click(presses, &e);
+                    self.register_false_start(e.term_id, term_id, e.timestamp);
+                    if self
+                        .current
+                        .false_starts
+                        .locked_until
+                        .contains_key(&e.term_id)
+                    {
+                        self.set_term_light(e.term_id, LIGHT_LOCKED_OUT);
+                    }
+                }
+                ClickKind::FalseStart => log::debug!("Early release. Skipping: {:?}", e),
+                ClickKind::LockedOut => log::info!("Player is locked out. Event {:?}", e),
+                ClickKind::InTime => {
+                    log::info!("After answer allowed. Event {:?}", e);
+                    in_time_events.push(e);
+                }
+            }
+        }
+        in_time_events
+    }
+
+    /// Lifts the lockouts which are over by the given hub timestamp
+    pub(crate) fn release_expired_lockouts(&mut self, timestamp: u32) {
+        let expired: Vec<u8> = self
+            .current
+            .false_starts
+            .locked_until
+            .iter()
+            .filter(|(_, &until)| until <= timestamp)
+            .map(|(&id, _)| id)
+            .collect();
+
+        for player_id in expired {
+            log::info!("Player {} lockout is over", player_id);
+            self.current.false_starts.locked_until.remove(&player_id);
+            self.set_term_light(player_id, LIGHT_OFF);
+        }
+    }
+
+    /// Lifts all lockouts once the question is over
+    pub(crate) fn release_all_lockouts(&mut self) {
+        for player_id in self.current.false_starts.locked_out_players() {
+            self.set_term_light(player_id, LIGHT_OFF);
+        }
+        self.current.false_starts = FalseStarts::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::game_entities::Player;
+    use crate::hub_comm::hw::internal::api_types::TermButtonState;
+
+    fn press(term_id: u8, timestamp: u32) -> TermEvent {
+        TermEvent {
+            term_id,
+            timestamp,
+            state: TermButtonState::Pressed,
+        }
+    }
+
+    #[test]
+    fn test_false_start_locks_player_out() {
+        let mut ctx = GameContext::default();
+        ctx.players.insert(1, Player::new(1));
+        ctx.players.insert(2, Player::new(2));
+        ctx.set_false_start_penalty(FalseStartPenalty::Lockout(500));
+        ctx.current.false_starts.window_start = Some(100);
+
+        let events = vec![press(1, 50), press(1, 900), press(2, 1200), press(1, 1600)];
+        let events = ctx.filter_false_starts(events.clone(), &events, 1000);
+
+        assert_eq!(ctx.players[&1].stats.false_start_num, 1);
+        assert!(ctx.current.false_starts.is_locked_out(1, 1200));
+        assert_eq!(events, vec![press(2, 1200), press(1, 1600)]);
+
+        ctx.release_expired_lockouts(1500);
+        assert!(ctx.current.false_starts.locked_out_players().is_empty());
+    }
+}
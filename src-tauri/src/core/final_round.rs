@@ -0,0 +1,504 @@
+use error_stack::{Report, Result, ResultExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::core::game_entities::{GameContext, GameplayError, PlayerState};
+use crate::core::game_journal::JournalEvent;
+use crate::core::game_state_machine::GameCommand;
+use crate::game_pack::pack_content_entities::{Question, QuestionId};
+
+pub const FINAL_ROUND_TYPE: &str = "final";
+
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum FinalRoundStage {
+    #[default]
+    ThemeElimination,
+    Wagering,
+    Answering,
+    Judging,
+    Finished,
+}
+
+/// Final round flow.
+///
+/// Players eliminate themes in turn until a single one is left. Then every participant places
+/// a hidden wager of up to their score and submits a private answer. Answers are judged one
+/// by one in the participants order and the scores are settled once the last answer is judged.
+#[derive(Debug, Clone, Default)]
+pub struct FinalRound {
+    pub stage: FinalRoundStage,
+    /// Participants ordered by score, the lowest first
+    pub participants: Vec<u8>,
+    /// Theme names in the round order. Names may repeat, so themes are addressed by index
+    pub themes: Vec<String>,
+    pub eliminated_themes: Vec<usize>,
+    pub wagers: HashMap<u8, i32>,
+    pub answers: HashMap<u8, String>,
+    pub verdicts: HashMap<u8, bool>,
+    turn: usize,
+}
+
+impl FinalRound {
+    /// Final round without participants is over right away: nobody could play it
+    pub fn new(participants: Vec<u8>, themes: Vec<String>) -> Self {
+        let stage = if participants.is_empty() {
+            FinalRoundStage::Finished
+        } else if themes.len() > 1 {
+            FinalRoundStage::ThemeElimination
+        } else {
+            FinalRoundStage::Wagering
+        };
+
+        Self {
+            stage,
+            participants,
+            themes,
+            ..Default::default()
+        }
+    }
+
+    /// Indexes of the themes which are not eliminated yet
+    pub fn themes_left(&self) -> Vec<usize> {
+        (0..self.themes.len())
+            .filter(|index| !self.eliminated_themes.contains(index))
+            .collect()
+    }
+
+    /// Index of the remaining theme once the elimination is over
+    pub fn final_theme(&self) -> Option<usize> {
+        match self.themes_left().as_slice() {
+            [theme_index] => Some(*theme_index),
+            _ => None,
+        }
+    }
+
+    /// Player expected to act: the one who eliminates a theme or whose answer is judged next
+    pub fn current_player_id(&self) -> Option<u8> {
+        match self.stage {
+            FinalRoundStage::ThemeElimination => self
+                .participants
+                .get(self.turn % self.participants.len().max(1))
+                .copied(),
+            FinalRoundStage::Judging => self
+                .participants
+                .iter()
+                .find(|&id| !self.verdicts.contains_key(id))
+                .copied(),
+            _ => None,
+        }
+    }
+
+    pub fn eliminate_theme(
+        &mut self,
+        player_id: u8,
+        theme_index: usize,
+    ) -> Result<(), GameplayError> {
+        self.expect_stage(FinalRoundStage::ThemeElimination)?;
+        self.expect_current_player(player_id)?;
+
+        if !self.themes_left().contains(&theme_index) {
+            return Err(Report::new(GameplayError::PackElementNotPresent)).attach_printable(
+                format!("Theme #{theme_index} is not available for elimination"),
+            );
+        }
+
+        log::info!(
+            "Player {} eliminates theme #{} {:?}",
+            player_id,
+            theme_index,
+            self.themes[theme_index]
+        );
+        self.eliminated_themes.push(theme_index);
+        self.turn += 1;
+
+        if self.themes_left().len() == 1 {
+            log::info!("Final theme: {:?}", self.final_theme());
+            self.stage = FinalRoundStage::Wagering;
+        }
+        Ok(())
+    }
+
+    pub fn place_wager(
+        &mut self,
+        player_id: u8,
+        player_score: i32,
+        wager: i32,
+    ) -> Result<(), GameplayError> {
+        self.expect_stage(FinalRoundStage::Wagering)?;
+        self.expect_participant(player_id)?;
+
+        if wager < 1 || wager > player_score {
+            return Err(Report::new(GameplayError::BidNotAllowed)).attach_printable(format!(
+                "Wager of player {} has to be in range 1..={}. Got: {}",
+                player_id, player_score, wager
+            ));
+        }
+
+        self.wagers.insert(player_id, wager);
+        if self.wagers.len() == self.participants.len() {
+            log::info!("All wagers are placed");
+            self.stage = FinalRoundStage::Answering;
+        }
+        Ok(())
+    }
+
+    pub fn submit_answer(&mut self, player_id: u8, answer: String) -> Result<(), GameplayError> {
+        self.expect_stage(FinalRoundStage::Answering)?;
+        self.expect_participant(player_id)?;
+
+        self.answers.insert(player_id, answer);
+        if self.answers.len() == self.participants.len() {
+            log::info!("All answers are submitted");
+            self.stage = FinalRoundStage::Judging;
+        }
+        Ok(())
+    }
+
+    pub fn judge_answer(
+        &mut self,
+        player_id: u8,
+        answered_correctly: bool,
+    ) -> Result<(), GameplayError> {
+        self.expect_stage(FinalRoundStage::Judging)?;
+        self.expect_current_player(player_id)?;
+
+        self.verdicts.insert(player_id, answered_correctly);
+        if self.verdicts.len() == self.participants.len() {
+            self.stage = FinalRoundStage::Finished;
+        }
+        Ok(())
+    }
+
+    /// Score change of every participant. Available once all answers are judged
+    pub fn settlement(&self) -> Vec<(u8, i32)> {
+        if self.stage != FinalRoundStage::Finished {
+            return vec![];
+        }
+
+        self.participants
+            .iter()
+            .map(|id| {
+                let wager = self.wagers.get(id).copied().unwrap_or_default();
+                match self.verdicts.get(id) {
+                    Some(true) => (*id, wager),
+                    _ => (*id, -wager),
+                }
+            })
+            .collect()
+    }
+
+    fn expect_stage(&self, stage: FinalRoundStage) -> Result<(), GameplayError> {
+        if self.stage != stage {
+            return Err(Report::new(GameplayError::OperationForbidden)).attach_printable(format!(
+                "Final round stage is {:?}, expected {:?}",
+                self.stage, stage
+            ));
+        }
+        Ok(())
+    }
+
+    fn expect_participant(&self, player_id: u8) -> Result<(), GameplayError> {
+        if !self.participants.contains(&player_id) {
+            return Err(Report::new(GameplayError::PlayerNotPresent))
+                .attach_printable(format!("Player {} doesn't play the final round", player_id));
+        }
+        Ok(())
+    }
+
+    fn expect_current_player(&self, player_id: u8) -> Result<(), GameplayError> {
+        if self.current_player_id() != Some(player_id) {
+            return Err(Report::new(GameplayError::OperationForbidden)).attach_printable(format!(
+                "It's turn of player {:?}, not {}",
+                self.current_player_id(),
+                player_id
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl GameContext {
+    pub fn is_final_round(&self) -> bool {
+        self.get_current_round().round_type == FINAL_ROUND_TYPE
+    }
+
+    pub fn start_final_round(&mut self) {
+        let rules = self.rules();
+        let mut participants: Vec<(u8, i32)> = self
+            .players
+            .values()
+            .filter(|&p| p.state != PlayerState::Dead && rules.plays_final(p))
+            .map(|p| (p.term_id, p.stats.score))
+            .collect();
+        participants.sort_by_key(|&(id, score)| (score, id));
+
+        self.players.values_mut().for_each(|p| {
+            if p.state != PlayerState::Dead && !participants.iter().any(|&(id, _)| id == p.term_id)
+            {
+                log::info!(
+                    "Player {} doesn't have points to play the final round",
+                    p.term_id
+                );
+                p.state = PlayerState::Inactive;
+            }
+        });
+
+        let round = self.get_current_round();
+        let themes: Vec<String> = round.themes.iter().map(|t| t.name.clone()).collect();
+
+        let participants = participants.iter().map(|&(id, _)| id).collect();
+        log::info!(
+            "Starting final round. Participants: {:?}, themes: {:?}",
+            participants,
+            themes
+        );
+        self.current.final_round = Some(FinalRound::new(participants, themes));
+    }
+
+    pub fn get_final_round(&self) -> Result<&FinalRound, GameplayError> {
+        self.current
+            .final_round
+            .as_ref()
+            .ok_or(Report::new(GameplayError::OperationForbidden))
+            .attach_printable("Final round is not started")
+    }
+
+    pub fn eliminate_final_theme(
+        &mut self,
+        player_id: u8,
+        theme_index: usize,
+    ) -> Result<(), GameplayError> {
+        self.get_final_round_mut()?
+            .eliminate_theme(player_id, theme_index)?;
+        self.journal.record(JournalEvent::FinalThemeEliminated {
+            player_id,
+            theme_index,
+        });
+        Ok(())
+    }
+
+    pub fn place_final_wager(&mut self, player_id: u8, wager: i32) -> Result<(), GameplayError> {
+        let score = self.get_player_score(player_id)?;
+        self.get_final_round_mut()?
+            .place_wager(player_id, score, wager)?;
+        self.journal
+            .record(JournalEvent::FinalWagerPlaced { player_id, wager });
+        Ok(())
+    }
+
+    /// Final question becomes available once all wagers are placed
+    pub fn get_final_question(&mut self) -> Result<(String, Question), GameplayError> {
+        self.ensure_command_allowed(GameCommand::PlayFinalRound)?;
+        let final_round = self.get_final_round()?;
+        if final_round.stage == FinalRoundStage::ThemeElimination
+            || final_round.stage == FinalRoundStage::Wagering
+        {
+            return Err(Report::new(GameplayError::OperationForbidden)).attach_printable(format!(
+                "Final question is hidden during {:?} stage",
+                final_round.stage
+            ));
+        }
+
+        let theme_index = final_round
+            .final_theme()
+            .ok_or(Report::new(GameplayError::PackElementNotPresent))
+            .attach_printable("Final theme is not selected")?;
+
+        let round_index = self.current.round_index;
+        let theme = self
+            .get_current_round()
+            .themes
+            .get(theme_index)
+            .ok_or(Report::new(GameplayError::PackElementNotPresent))
+            .attach_printable(format!("Can't find final theme #{theme_index}"))?;
+        let theme_name = theme.name.clone();
+        let (id, question) = theme
+            .remaining_questions()
+            .next()
+            .map(|(index, question)| {
+                let id = QuestionId {
+                    round: round_index,
+                    theme: theme_index,
+                    question: index,
+                };
+                (id, question.clone())
+            })
+            .ok_or(Report::new(GameplayError::PackElementNotPresent))
+            .attach_printable(format!("Final theme {theme_name:?} has no question"))?;
+
+        self.current.question_id = id;
+        self.current.question_theme = theme_name.clone();
+        self.current.question_nominal_price = question.price;
+        self.current.question_type = question.question_type.clone();
+        self.current.answer_scene = question.answer_scenario.clone();
+        self.current.host_notes = question.host_notes.clone();
+        self.journal.record(JournalEvent::FinalQuestionShown);
+        Ok((theme_name, question))
+    }
+
+    pub fn submit_final_answer(
+        &mut self,
+        player_id: u8,
+        answer: String,
+    ) -> Result<(), GameplayError> {
+        self.get_final_round_mut()?
+            .submit_answer(player_id, answer.clone())?;
+        self.journal
+            .record(JournalEvent::FinalAnswerSubmitted { player_id, answer });
+        Ok(())
+    }
+
+    pub fn judge_final_answer(
+        &mut self,
+        player_id: u8,
+        answered_correctly: bool,
+    ) -> Result<(), GameplayError> {
+        let scores_before = self.player_scores();
+        // Judged on a copy, so the verdict is dropped if the final question can't be closed
+        let mut final_round = self.get_final_round_mut()?.clone();
+        final_round.judge_answer(player_id, answered_correctly)?;
+
+        let settlement = final_round.settlement();
+        let judged = JournalEvent::FinalAnswerJudged {
+            player_id,
+            answered_correctly,
+        };
+        if settlement.is_empty() {
+            self.current.final_round = Some(final_round);
+            self.journal.record(judged);
+            return Ok(());
+        }
+
+        self.remove_question(self.current.question_id)
+            .change_context(GameplayError::PackElementNotPresent)?;
+        self.current.final_round = Some(final_round);
+
+        log::info!("Final round is over. Settling scores: {:?}", settlement);
+        for (id, score_change) in settlement {
+            let Some(player) = self.players.get_mut(&id) else {
+                continue;
+            };
+
+            player.stats.score += score_change;
+            player.stats.total_tries += 1;
+            self.current.total_tries += 1;
+            if score_change > 0 {
+                player.stats.correct_num += 1;
+                player.state = PlayerState::AnsweredCorrectly;
+                self.current.total_correct_answers += 1;
+            } else {
+                player.stats.wrong_num += 1;
+                player.state = PlayerState::AnsweredWrong;
+                self.current.total_wrong_answers += 1;
+            }
+        }
+
+        self.journal.record(judged);
+        self.journal_score_changes(&scores_before);
+        Ok(())
+    }
+
+    /// Final round is played from the round board, so every mutation is checked against it
+    fn get_final_round_mut(&mut self) -> Result<&mut FinalRound, GameplayError> {
+        self.ensure_command_allowed(GameCommand::PlayFinalRound)?;
+        self.current
+            .final_round
+            .as_mut()
+            .ok_or(Report::new(GameplayError::OperationForbidden))
+            .attach_printable("Final round is not started")
+    }
+
+    fn get_player_score(&self, player_id: u8) -> Result<i32, GameplayError> {
+        let player = self
+            .players
+            .get(&player_id)
+            .ok_or(Report::new(GameplayError::PlayerNotPresent))
+            .attach_printable(format!("Can't find player with id {}", player_id))?;
+        Ok(player.stats.score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::game_entities::{GameState, Player};
+    use crate::game_pack::pack_content_entities::test_pack::{make_pack, make_round, make_theme};
+
+    #[test]
+    fn test_final_round_flow() {
+        let themes = vec!["A".to_string(), "B".to_string(), "A".to_string()];
+        let mut final_round = FinalRound::new(vec![2, 1], themes);
+
+        assert!(final_round.eliminate_theme(1, 0).is_err());
+        final_round.eliminate_theme(2, 0).expect("Test");
+        assert!(final_round.eliminate_theme(1, 0).is_err());
+        assert!(final_round.eliminate_theme(1, 3).is_err());
+        assert_eq!(final_round.themes_left(), vec![1, 2]);
+        final_round.eliminate_theme(1, 1).expect("Test");
+        assert_eq!(final_round.stage, FinalRoundStage::Wagering);
+        assert_eq!(final_round.final_theme(), Some(2));
+
+        assert!(final_round.place_wager(2, 100, 200).is_err());
+        final_round.place_wager(2, 100, 100).expect("Test");
+        final_round.place_wager(1, 500, 300).expect("Test");
+        assert_eq!(final_round.stage, FinalRoundStage::Answering);
+
+        final_round
+            .submit_answer(1, "Answer".to_string())
+            .expect("Test");
+        final_round.submit_answer(2, "".to_string()).expect("Test");
+        assert_eq!(final_round.stage, FinalRoundStage::Judging);
+
+        assert!(final_round.judge_answer(1, true).is_err());
+        final_round.judge_answer(2, false).expect("Test");
+        assert!(final_round.settlement().is_empty());
+        final_round.judge_answer(1, true).expect("Test");
+
+        assert_eq!(final_round.stage, FinalRoundStage::Finished);
+        assert_eq!(final_round.settlement(), vec![(2, -100), (1, 300)]);
+    }
+
+    #[test]
+    fn test_final_verdict_is_dropped_if_question_is_missing() {
+        let mut ctx = GameContext::default();
+        ctx.game_pack = make_pack(vec![make_round(vec![make_theme("Final", &[100])])]);
+        ctx.players.insert(1, Player::new(1));
+        ctx.players.get_mut(&1).expect("Test").stats.score = 500;
+        ctx.current.set_game_state(GameState::QuestionChoosing);
+
+        let mut final_round = FinalRound::new(vec![1], vec!["Final".to_string()]);
+        final_round.place_wager(1, 500, 300).expect("Test");
+        final_round
+            .submit_answer(1, "Answer".to_string())
+            .expect("Test");
+        ctx.current.final_round = Some(final_round);
+        ctx.current.question_id = QuestionId {
+            question: 1,
+            ..Default::default()
+        };
+
+        assert!(ctx.judge_final_answer(1, true).is_err());
+        assert_eq!(ctx.players[&1].stats.score, 500);
+        let final_round = ctx.get_final_round().expect("Test");
+        assert_eq!(final_round.stage, FinalRoundStage::Judging);
+        assert!(final_round.verdicts.is_empty());
+
+        ctx.current.question_id = QuestionId::default();
+        ctx.judge_final_answer(1, true).expect("Test");
+        assert_eq!(ctx.players[&1].stats.score, 800);
+        assert_eq!(
+            ctx.get_final_round().expect("Test").stage,
+            FinalRoundStage::Finished
+        );
+    }
+
+    #[test]
+    fn test_final_round_without_participants_is_finished() {
+        let themes = vec!["A".to_string(), "B".to_string()];
+        let final_round = FinalRound::new(vec![], themes);
+
+        assert_eq!(final_round.stage, FinalRoundStage::Finished);
+        assert_eq!(final_round.current_player_id(), None);
+        assert!(final_round.settlement().is_empty());
+    }
+}
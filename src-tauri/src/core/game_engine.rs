@@ -0,0 +1,470 @@
+use error_stack::Result;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::time::Duration;
+
+use crate::core::answer_timer::start_answer_timer_watchdog;
+use crate::core::auction::AuctionBidType;
+use crate::core::buzzer_arbiter::spawn_buzzer_arbiter;
+use crate::core::game_entities::{GameContext, GameplayError};
+use crate::core::game_events::GameEvent;
+use crate::core::game_save::{GameSave, GameSaveError};
+use crate::core::score_adjustment::ScoreAdjustmentType;
+use crate::core::tie_break::TieBreakSource;
+use crate::game_pack::game_pack_entites::GamePack;
+use crate::game_pack::pack_content_entities::{Question, QuestionId};
+use crate::hub_comm::hw::internal::api_types::TermEvent;
+
+/// Host command changing the game
+#[derive(Debug, Clone, PartialEq)]
+pub enum EngineCommand {
+    StartGame,
+    SelectQuestion {
+        id: QuestionId,
+    },
+    PassPigInPoke {
+        player_id: u8,
+    },
+    PlaceAuctionBid {
+        player_id: u8,
+        bid_type: AuctionBidType,
+        amount: i32,
+    },
+    AllowAnswer,
+    JudgeAnswer {
+        answered_correctly: bool,
+    },
+    FinishQuestion,
+    ExtendAnswerTimer {
+        extra_time: Duration,
+    },
+    CancelAnswerTimer,
+    StartNextRound,
+    EliminateFinalTheme {
+        player_id: u8,
+        theme_index: usize,
+    },
+    PlaceFinalWager {
+        player_id: u8,
+        wager: i32,
+    },
+    ShowFinalQuestion,
+    SubmitFinalAnswer {
+        player_id: u8,
+        answer: String,
+    },
+    JudgeFinalAnswer {
+        player_id: u8,
+        answered_correctly: bool,
+    },
+    AdjustScore {
+        player_id: u8,
+        adjustment_type: ScoreAdjustmentType,
+        amount: i32,
+        reason: String,
+    },
+    Undo,
+    Redo,
+    SetRoundTimerPaused {
+        paused: bool,
+    },
+    StartTieBreak {
+        source: TieBreakSource,
+    },
+}
+
+/// Data the command produces besides the events
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandOutput {
+    Done,
+    Question {
+        theme: String,
+        question: Box<Question>,
+        number: i32,
+    },
+    /// Whether other players may still answer the question
+    AnswerJudged {
+        retry: bool,
+    },
+    ScoreAdjusted {
+        score: i32,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct EngineResponse {
+    pub output: CommandOutput,
+    /// What has changed in the game. Events are pushed to the event sink as well
+    pub events: Vec<GameEvent>,
+}
+
+/// Self-contained game which owns its context, hub and pack.
+///
+/// Clones share the same game. Every engine is a separate game: several games can run in
+/// one process
+#[derive(Debug, Clone)]
+pub struct GameEngine {
+    context: Arc<Mutex<GameContext>>,
+    /// Answer attempt the countdown watchdog was started for
+    watched_answer_attempt: Arc<AtomicU32>,
+}
+
+/// Engine handle of the background threads. It doesn't keep the game alive, so the threads
+/// stop once the engine is dropped
+#[derive(Debug, Clone)]
+pub(crate) struct WeakGameEngine {
+    context: Weak<Mutex<GameContext>>,
+    watched_answer_attempt: Weak<AtomicU32>,
+}
+
+impl WeakGameEngine {
+    pub(crate) fn upgrade(&self) -> Option<GameEngine> {
+        Some(GameEngine {
+            context: self.context.upgrade()?,
+            watched_answer_attempt: self.watched_answer_attempt.upgrade()?,
+        })
+    }
+}
+
+impl Default for GameEngine {
+    fn default() -> Self {
+        Self::new(GameContext::default(), None)
+    }
+}
+
+impl GameEngine {
+    /// The game is written to the data dir after each change, so it can be resumed.
+    /// Without the data dir the game is kept in memory only
+    pub fn new(mut context: GameContext, data_dir: Option<PathBuf>) -> Self {
+        context.data_dir = data_dir;
+        Self {
+            context: Arc::new(Mutex::new(context)),
+            watched_answer_attempt: Arc::new(AtomicU32::default()),
+        }
+    }
+
+    pub(crate) fn downgrade(&self) -> WeakGameEngine {
+        WeakGameEngine {
+            context: Arc::downgrade(&self.context),
+            watched_answer_attempt: Arc::downgrade(&self.watched_answer_attempt),
+        }
+    }
+
+    /// Locks the game for queries and setup. Changes made through the context are not
+    /// published: use `execute` to play the game
+    pub fn context(&self) -> MutexGuard<'_, GameContext> {
+        self.context.lock().expect("Mutex is poisoned")
+    }
+
+    /// Changes the game setup and shows it on the scoreboard. Setup is not played, so it
+    /// is neither saved nor journaled
+    pub fn setup<T>(&self, change: impl FnOnce(&mut GameContext) -> T) -> T {
+        let mut ctx = self.context();
+        let result = change(&mut ctx);
+        ctx.publish_scoreboard();
+        result
+    }
+
+    pub fn execute(&self, command: EngineCommand) -> Result<EngineResponse, GameplayError> {
+        log::debug!("Executing {:?}", command);
+        let mut ctx = self.context();
+        let output = match command {
+            EngineCommand::StartGame => {
+                ctx.start_the_game()?;
+                self.watched_answer_attempt.store(0, Ordering::Relaxed);
+                self.start_buzzer_arbiter(&mut ctx);
+                CommandOutput::Done
+            }
+            EngineCommand::SelectQuestion { id } => {
+                let (question, number) = ctx.get_pack_question(id)?;
+                CommandOutput::Question {
+                    theme: ctx.current.question_theme.clone(),
+                    question: Box::new(question),
+                    number,
+                }
+            }
+            EngineCommand::PassPigInPoke { player_id } => {
+                ctx.set_pip_victim(player_id)?;
+                CommandOutput::Done
+            }
+            EngineCommand::PlaceAuctionBid {
+                player_id,
+                bid_type,
+                amount,
+            } => {
+                ctx.place_auction_bid(player_id, bid_type, amount)?;
+                CommandOutput::Done
+            }
+            EngineCommand::AllowAnswer => {
+                ctx.allow_answer()?;
+                CommandOutput::Done
+            }
+            EngineCommand::JudgeAnswer { answered_correctly } => {
+                let retry = ctx.answer_question(answered_correctly)?;
+                CommandOutput::AnswerJudged { retry }
+            }
+            EngineCommand::FinishQuestion => {
+                ctx.finish_question_prematurely()?;
+                CommandOutput::Done
+            }
+            EngineCommand::ExtendAnswerTimer { extra_time } => {
+                ctx.extend_answer_timer(extra_time)?;
+                CommandOutput::Done
+            }
+            EngineCommand::CancelAnswerTimer => {
+                ctx.cancel_answer_timer();
+                CommandOutput::Done
+            }
+            EngineCommand::StartNextRound => {
+                ctx.init_next_round()?;
+                CommandOutput::Done
+            }
+            EngineCommand::EliminateFinalTheme {
+                player_id,
+                theme_index,
+            } => {
+                ctx.eliminate_final_theme(player_id, theme_index)?;
+                CommandOutput::Done
+            }
+            EngineCommand::PlaceFinalWager { player_id, wager } => {
+                ctx.place_final_wager(player_id, wager)?;
+                CommandOutput::Done
+            }
+            EngineCommand::ShowFinalQuestion => {
+                let (theme, question) = ctx.get_final_question()?;
+                CommandOutput::Question {
+                    theme,
+                    question: Box::new(question),
+                    number: 0,
+                }
+            }
+            EngineCommand::SubmitFinalAnswer { player_id, answer } => {
+                ctx.submit_final_answer(player_id, answer)?;
+                CommandOutput::Done
+            }
+            EngineCommand::JudgeFinalAnswer {
+                player_id,
+                answered_correctly,
+            } => {
+                ctx.judge_final_answer(player_id, answered_correctly)?;
+                CommandOutput::Done
+            }
+            EngineCommand::AdjustScore {
+                player_id,
+                adjustment_type,
+                amount,
+                reason,
+            } => {
+                let score = ctx.adjust_player_score(player_id, adjustment_type, amount, reason)?;
+                CommandOutput::ScoreAdjusted { score }
+            }
+            EngineCommand::Undo => {
+                ctx.undo_last_action()?;
+                CommandOutput::Done
+            }
+            EngineCommand::Redo => {
+                ctx.redo_last_action()?;
+                CommandOutput::Done
+            }
+            EngineCommand::SetRoundTimerPaused { paused } => {
+                ctx.set_round_timer_paused(paused);
+                CommandOutput::Done
+            }
+            EngineCommand::StartTieBreak { source } => {
+                ctx.start_tie_break(source)?;
+                CommandOutput::Done
+            }
+        };
+
+        let events = self.commit(&mut ctx);
+        Ok(EngineResponse { output, events })
+    }
+
+    /// Takes the clicks received from the hub. Called by the buzzer arbiter, may be called
+    /// directly to play without a hub
+    pub fn process_clicks(&self, clicks: Vec<TermEvent>) -> Vec<GameEvent> {
+        let mut ctx = self.context();
+        let players_before = ctx.players.clone();
+        let lockouts_before = ctx.current.false_starts.locked_out_players();
+
+        let mut events = vec![];
+        match ctx.arbitrate_clicks(clicks) {
+            Some(winner) => {
+                let winner_event = GameEvent::BuzzerWinner(winner);
+                ctx.emit_event(winner_event.clone());
+                events.push(winner_event);
+            }
+            // False starts and lockouts change the players without a winner
+            None if ctx.players == players_before
+                && ctx.current.false_starts.locked_out_players() == lockouts_before =>
+            {
+                return events;
+            }
+            None => {}
+        }
+
+        events.extend(self.commit(&mut ctx));
+        events
+    }
+
+    /// Restores the saved game and connects it to the hub
+    pub fn resume_game(
+        &self,
+        save: GameSave,
+        pack: GamePack,
+    ) -> Result<Vec<GameEvent>, GameSaveError> {
+        let mut ctx = self.context();
+        let journal_path = save.journal_path.clone();
+        ctx.restore_game(save, pack)?;
+        ctx.journal_game_resume(journal_path.as_deref());
+        self.watched_answer_attempt.store(0, Ordering::Relaxed);
+        ctx.connect_hub_events();
+        self.start_buzzer_arbiter(&mut ctx);
+        Ok(self.commit(&mut ctx))
+    }
+
+    /// Persists and publishes the changes made by the command
+    pub(crate) fn commit(&self, ctx: &mut GameContext) -> Vec<GameEvent> {
+        ctx.save_game();
+
+        if let Some(timer) = &ctx.current.answer_timer {
+            let previous = self
+                .watched_answer_attempt
+                .swap(timer.attempt, Ordering::Relaxed);
+            if previous != timer.attempt {
+                start_answer_timer_watchdog(self.downgrade(), timer.attempt);
+            }
+        }
+
+        let events = ctx.collect_changes();
+        events.iter().for_each(|e| ctx.emit_event(e.clone()));
+        if !events.is_empty() {
+            ctx.publish_scoreboard();
+        }
+        events
+    }
+
+    /// Hands the hub event queue over to the background arbiter
+    fn start_buzzer_arbiter(&self, ctx: &mut GameContext) {
+        let Some(receiver) = ctx.event_queue.take() else {
+            log::error!("Hub events are not connected. Buzzers won't work");
+            return;
+        };
+
+        ctx.buzzer_session += 1;
+        spawn_buzzer_arbiter(self.downgrade(), ctx.buzzer_session, receiver);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::game_entities::{GameState, Player};
+    use crate::core::game_save::read_game_save;
+    use crate::core::scoreboard::ScoreboardFeed;
+    use crate::game_pack::pack_content_entities::test_pack::{make_pack, make_round, make_theme};
+    use crate::hub_comm::hw::hw_hub_manager::get_epoch_ms;
+    use crate::hub_comm::hw::internal::api_types::TermButtonState;
+
+    fn make_engine(data_dir: Option<PathBuf>) -> GameEngine {
+        let mut ctx = GameContext::default();
+        ctx.game_pack = make_pack(vec![make_round(vec![make_theme("Music", &[100])])]);
+        ctx.players.insert(1, Player::new(1));
+        ctx.players.insert(2, Player::new(2));
+        ctx.begin_game().expect("Test");
+        ctx.assign_first_chooser(1).expect("Test");
+        GameEngine::new(ctx, data_dir)
+    }
+
+    #[test]
+    fn test_engine_plays_question_without_hub() {
+        let engine = make_engine(None);
+        let response = engine
+            .execute(EngineCommand::SelectQuestion {
+                id: QuestionId::default(),
+            })
+            .expect("Test");
+        assert!(matches!(response.output, CommandOutput::Question { .. }));
+        assert!(response
+            .events
+            .iter()
+            .any(|e| e.name() == "game-state-changed"));
+
+        engine.execute(EngineCommand::AllowAnswer).expect("Test");
+        let click = TermEvent {
+            term_id: 2,
+            timestamp: get_epoch_ms().expect("Test"),
+            state: TermButtonState::Pressed,
+        };
+        let events = engine.process_clicks(vec![click]);
+        assert_eq!(events[0].name(), "buzzer-winner");
+
+        let response = engine
+            .execute(EngineCommand::JudgeAnswer {
+                answered_correctly: true,
+            })
+            .expect("Test");
+        assert_eq!(
+            response.output,
+            CommandOutput::AnswerJudged { retry: false }
+        );
+
+        let ctx = engine.context();
+        assert_eq!(ctx.players[&2].stats.score, 100);
+        assert_eq!(*ctx.current.game_state(), GameState::QuestionChoosing);
+    }
+
+    #[test]
+    fn test_false_start_is_saved_and_published() {
+        let data_dir = tempfile::tempdir().expect("Test");
+        let engine = make_engine(Some(data_dir.path().to_path_buf()));
+        engine
+            .execute(EngineCommand::SelectQuestion {
+                id: QuestionId::default(),
+            })
+            .expect("Test");
+        assert!(engine.process_clicks(vec![]).is_empty());
+
+        let click = TermEvent {
+            term_id: 2,
+            timestamp: get_epoch_ms().expect("Test"),
+            state: TermButtonState::Pressed,
+        };
+        let events = engine.process_clicks(vec![click]);
+        assert!(events.iter().any(|e| e.name() == "players-changed"));
+
+        let save = read_game_save(data_dir.path()).expect("Test");
+        assert_eq!(save.players[&2].stats.false_start_num, 1);
+    }
+
+    #[test]
+    fn test_engine_saves_game_to_data_dir() {
+        let data_dir = tempfile::tempdir().expect("Test");
+        let engine = make_engine(Some(data_dir.path().to_path_buf()));
+        engine
+            .execute(EngineCommand::SelectQuestion {
+                id: QuestionId::default(),
+            })
+            .expect("Test");
+
+        let save = read_game_save(data_dir.path()).expect("Test");
+        assert_eq!(save.players.len(), 2);
+
+        let weak = engine.downgrade();
+        assert!(weak.upgrade().is_some());
+        drop(engine);
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn test_setup_is_shown_on_scoreboard() {
+        let engine = GameEngine::default();
+        let feed = ScoreboardFeed::default();
+        engine.context().set_scoreboard(feed.clone());
+        assert!(feed.latest().players.is_empty());
+
+        engine.setup(|game| game.players.insert(1, Player::new(1)));
+        assert_eq!(feed.latest().players.len(), 1);
+    }
+}
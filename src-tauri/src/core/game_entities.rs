@@ -1,14 +1,30 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::atomic::AtomicU32;
-use std::sync::Mutex;
-use std::sync::{Arc, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Duration;
 
 use thiserror::Error;
 
 use std::sync::mpsc::Receiver;
 
 use crate::api::dto::QuestionType;
+use crate::core::answer_timer::AnswerTimer;
+use crate::core::auction::Auction;
+use crate::core::false_start::{FalseStartPenalty, FalseStarts};
+use crate::core::final_round::FinalRound;
+use crate::core::game_events::{GameEventSink, PublishedState};
+use crate::core::game_history::GameHistory;
+use crate::core::game_journal::GameJournal;
+use crate::core::game_state_machine::{GameCommand, StateTransition};
+use crate::core::round_timer::RoundTimer;
+use crate::core::rule_set::RuleSetPreset;
+use crate::core::scoreboard::ScoreboardFeed;
+use crate::core::score_adjustment::ScoreAdjustment;
+use crate::core::team::Team;
+use crate::core::tie_break::TieBreak;
 use crate::game_pack::game_pack_entites::GamePack;
+use crate::game_pack::pack_content_entities::{Atom, HostNotes, QuestionId};
 use crate::hub_comm::common::hub_api::{HubManager, HubType};
 use crate::hub_comm::hw::hw_hub_manager::HwHubManager;
 use crate::hub_comm::hw::internal::api_types::TermEvent;
@@ -16,14 +32,6 @@ use error_stack::Report;
 use serde::{Deserialize, Serialize};
 use crate::hub_comm::web::web_hub_manager::WebHubManager;
 
-lazy_static::lazy_static! {
-    static ref CONTEXT: Arc<Mutex<GameContext>> = Arc::new(Mutex::new(GameContext::default()));
-}
-
-pub fn game() -> MutexGuard<'static, GameContext> {
-    CONTEXT.lock().expect("Mutex is poisoned")
-}
-
 #[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum PlayerState {
     #[default]
@@ -43,6 +51,8 @@ pub struct PlayerStats {
     pub correct_num: i32,
     pub wrong_num: i32,
     pub total_tries: i32,
+    #[serde(default)]
+    pub false_start_num: i32,
 }
 
 #[derive(Debug, Default, Eq, Clone, PartialEq, Serialize, Deserialize)]
@@ -89,26 +99,55 @@ type HubManagerHandle = Arc<RwLock<Box<dyn HubManager>>>;
 #[derive(Debug)]
 pub struct GameContext {
     pub players: HashMap<u8, Player>,
+    /// Teams keyed by their player id. Empty in individual play
+    pub teams: HashMap<u8, Team>,
     pub game_pack: GamePack,
     pub hub_type: HubType,
     hub: HubManagerHandle,
     pub current: CurrentContext,
     pub event_queue: Option<Receiver<TermEvent>>,
     pub allow_answer_timestamp: Arc<AtomicU32>,
+    pub round_duration: Option<Duration>,
+    pub answer_window: Option<Duration>,
+    pub false_start_penalty: FalseStartPenalty,
+    pub rule_set: RuleSetPreset,
+    pub history: GameHistory,
+    pub score_adjustments: Vec<ScoreAdjustment>,
+    pub journal: GameJournal,
+    pub state_transitions: Vec<StateTransition>,
+    pub event_sink: Option<Box<dyn GameEventSink>>,
+    pub published: PublishedState,
+    pub scoreboard: Option<ScoreboardFeed>,
+    /// Identifies the buzzer arbiter of the current game
+    pub buzzer_session: u32,
+    /// Where the game is saved and journaled. Without it the game is kept in memory only
+    pub data_dir: Option<PathBuf>,
 }
 
-unsafe impl Send for GameContext {}
-
 impl Default for GameContext {
     fn default() -> Self {
         Self {
             hub_type: HubType::default(),
             hub: Arc::new(RwLock::new(Box::new(HwHubManager::default()))),
             players: HashMap::default(),
+            teams: HashMap::default(),
             game_pack: GamePack::default(),
             current: CurrentContext::default(),
             event_queue: None,
             allow_answer_timestamp: Arc::new(AtomicU32::default()),
+            round_duration: None,
+            answer_window: None,
+            false_start_penalty: FalseStartPenalty::default(),
+            rule_set: RuleSetPreset::default(),
+            history: GameHistory::default(),
+            score_adjustments: Vec::default(),
+            journal: GameJournal::default(),
+            state_transitions: Vec::default(),
+            event_sink: None,
+            published: PublishedState::default(),
+            scoreboard: None,
+            buzzer_session: 0,
+            data_dir: None,
         }
     }
 }
@@ -160,16 +199,37 @@ impl GameContext {
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct CurrentContext {
     pub round_index: usize,
     active_player_id: u8,
+    /// Player whose turn is to select the next question. Zero if nobody chooses
+    pub question_chooser_id: u8,
     game_state: GameState,
     pub click_for_answer_allowed: bool,
     pub answer_allowed: bool,
+    /// Where the current question is in the pack
+    pub question_id: QuestionId,
     pub question_theme: String,
+    /// Price the question is played for (may differ from the pack price for special questions)
     pub question_price: i32,
+    /// Price of the question in the pack
+    pub question_nominal_price: i32,
     pub question_type: QuestionType,
+    /// Answer illustration of the current question
+    pub answer_scene: Vec<Atom>,
+    pub host_notes: HostNotes,
+    pub auction: Option<Auction>,
+    pub final_round: Option<FinalRound>,
+    pub tie_break: Option<TieBreak>,
+    pub round_timer: RoundTimer,
+    pub answer_attempt: u32,
+    pub answer_timer: Option<AnswerTimer>,
+    pub false_starts: FalseStarts,
+    /// Teammate who won the click for the team
+    pub answering_term_id: Option<u8>,
+    /// Answers given on the question being played
+    pub question_tries: usize,
     pub total_correct_answers: i32,
     pub total_wrong_answers: i32,
     pub total_tries: i32,
@@ -202,6 +262,15 @@ pub enum GameplayError {
     AnswerForbidden,
     #[error("Operation forbidden")]
     OperationForbidden,
+    #[error("Bid not allowed")]
+    BidNotAllowed,
+    #[error("{command:?} is not allowed in {state:?} state")]
+    CommandNotAllowed {
+        command: GameCommand,
+        state: GameState,
+    },
+    #[error("Transition {from:?} -> {to:?} is not allowed")]
+    TransitionNotAllowed { from: GameState, to: GameState },
     #[error("Internal error")]
     InternalError,
 }
@@ -221,16 +290,34 @@ pub enum GameState {
 
 #[cfg(test)]
 mod game_entities_test {
-    use crate::core::game_entities::{GameContext, Player};
+    use crate::core::game_entities::{GameContext, GameState, Player};
+    use crate::hub_comm::hw::hw_hub_manager::get_epoch_ms;
+    use crate::hub_comm::hw::internal::api_types::{TermButtonState, TermEvent};
+    use std::sync::atomic::Ordering;
 
     #[test]
     fn test_fastest_click() {
         let mut ctx = GameContext::default();
-        ctx.players.insert(1, Player::default());
-        ctx.players.insert(2, Player::default());
-        ctx.players.insert(3, Player::default());
-        ctx.players.insert(4, Player::default());
-        let i = ctx.get_fastest_click_player_id().expect("Test");
-        log::info!("Fastest click from: {i}");
+        ctx.players.insert(1, Player::new(1));
+        ctx.players.insert(2, Player::new(2));
+        ctx.players.insert(3, Player::new(3));
+        ctx.players.insert(4, Player::new(4));
+        ctx.current.set_game_state(GameState::AnswerAllowed);
+        ctx.open_false_start_window();
+        let base = get_epoch_ms().expect("Test");
+        ctx.allow_answer_timestamp.swap(base, Ordering::Relaxed);
+
+        let click = |term_id, delay| TermEvent {
+            term_id,
+            timestamp: base + delay,
+            state: TermButtonState::Pressed,
+        };
+        let winner = ctx
+            .arbitrate_clicks(vec![click(3, 20), click(2, 10)])
+            .expect("Test");
+        log::info!("Fastest click from: {}", winner.playerId);
+        assert_eq!(winner.playerId, 2);
+        assert_eq!(winner.reactionMs, 10);
+        assert_eq!(*ctx.current.game_state(), GameState::AnswerRequested);
     }
 }
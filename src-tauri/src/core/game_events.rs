@@ -0,0 +1,133 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use crate::api::dto::{
+    ActivePlayerDto, AnswerTimeExpiredDto, BuzzerWinnerDto, GameStateDto, PlayerGameDto,
+    RoundProgressDto,
+};
+use crate::api::mapper::{map_game_state_to_dto, map_players_to_player_game_dto, map_round_to_dto};
+use crate::core::game_entities::{GameContext, GameState, Player};
+
+/// Notification pushed to the front ends when something happens in the game
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum GameEvent {
+    BuzzerWinner(BuzzerWinnerDto),
+    AnswerTimeExpired(AnswerTimeExpiredDto),
+    PlayersChanged(Vec<PlayerGameDto>),
+    ActivePlayerChanged(ActivePlayerDto),
+    GameStateChanged(GameStateDto),
+    RoundProgressChanged(RoundProgressDto),
+}
+
+impl GameEvent {
+    /// Name the front ends subscribe to
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameEvent::BuzzerWinner(_) => "buzzer-winner",
+            GameEvent::AnswerTimeExpired(_) => "answer-time-expired",
+            GameEvent::PlayersChanged(_) => "players-changed",
+            GameEvent::ActivePlayerChanged(_) => "active-player-changed",
+            GameEvent::GameStateChanged(_) => "game-state-changed",
+            GameEvent::RoundProgressChanged(_) => "round-progress-changed",
+        }
+    }
+}
+
+pub trait GameEventSink: Debug + Send + Sync {
+    fn emit(&self, event: &GameEvent);
+}
+
+/// Game state the front ends were told about last
+#[derive(Debug, Default, Clone)]
+pub struct PublishedState {
+    players: HashMap<u8, Player>,
+    active_player_id: u8,
+    game_state: GameState,
+    question_chooser_id: u8,
+    /// Round index, questions left and whether the round goes on
+    round_progress: Option<(usize, i32, bool)>,
+}
+
+impl GameContext {
+    pub fn set_event_sink(&mut self, sink: Box<dyn GameEventSink>) {
+        log::info!("Game events are pushed to: {:?}", sink);
+        self.event_sink = Some(sink);
+    }
+
+    pub(crate) fn emit_event(&self, event: GameEvent) {
+        log::debug!("Game event {}: {:?}", event.name(), event);
+        if let Some(sink) = &self.event_sink {
+            sink.emit(&event);
+        }
+    }
+
+    /// Collects what has changed since the last call.
+    /// Called once the command is done, so intermediate states are not pushed
+    pub(crate) fn collect_changes(&mut self) -> Vec<GameEvent> {
+        let mut events = vec![];
+
+        if self.players != self.published.players {
+            self.published.players = self.players.clone();
+            events.push(GameEvent::PlayersChanged(map_players_to_player_game_dto(
+                &self.players,
+            )));
+        }
+
+        let active_player_id = self.current.active_player_id();
+        if active_player_id != self.published.active_player_id {
+            self.published.active_player_id = active_player_id;
+            events.push(GameEvent::ActivePlayerChanged(ActivePlayerDto {
+                playerId: active_player_id as i32,
+            }));
+        }
+
+        let question_chooser_id = self.current.question_chooser_id;
+        if *self.current.game_state() != self.published.game_state
+            || question_chooser_id != self.published.question_chooser_id
+        {
+            self.published.game_state = self.current.game_state().clone();
+            self.published.question_chooser_id = question_chooser_id;
+            events.push(GameEvent::GameStateChanged(map_game_state_to_dto(self)));
+        }
+
+        let round_index = self.current.round_index;
+        if let Some(round) = self.game_pack.content.rounds.get(round_index) {
+            let progress = (round_index, round.questions_left, self.has_next_question());
+            if Some(progress) != self.published.round_progress {
+                self.published.round_progress = Some(progress);
+                events.push(GameEvent::RoundProgressChanged(RoundProgressDto {
+                    roundIndex: round_index,
+                    questionsLeft: round.questions_left,
+                    hasNextQuestion: progress.2,
+                    round: map_round_to_dto(round_index, round),
+                }));
+            }
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_pack::pack_content_entities::Round;
+
+    #[test]
+    fn test_only_changes_are_published() {
+        let mut ctx = GameContext::default();
+        ctx.game_pack.content.rounds.push(Round::default());
+        ctx.players.insert(1, Player::new(1));
+
+        let names: Vec<&str> = ctx.collect_changes().iter().map(|e| e.name()).collect();
+        assert_eq!(names, vec!["players-changed", "round-progress-changed"]);
+        assert!(ctx.collect_changes().is_empty());
+
+        ctx.players.get_mut(&1).expect("Test").stats.score = 100;
+        ctx.current.set_game_state(GameState::QuestionChoosing);
+        let names: Vec<&str> = ctx.collect_changes().iter().map(|e| e.name()).collect();
+        assert_eq!(names, vec!["players-changed", "game-state-changed"]);
+    }
+}
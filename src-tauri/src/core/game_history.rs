@@ -0,0 +1,250 @@
+use error_stack::{Report, Result, ResultExt};
+use std::collections::HashMap;
+
+use crate::core::game_entities::{CurrentContext, GameContext, GameState, GameplayError, Player};
+use crate::core::game_journal::JournalEvent;
+use crate::core::game_state_machine::GameCommand;
+use crate::core::score_adjustment::ScoreAdjustment;
+use crate::core::team::Team;
+use crate::game_pack::pack_content_entities::Round;
+
+/// State of the game a host action can change
+#[derive(Debug, Clone)]
+pub struct GameSnapshot {
+    pub players: HashMap<u8, Player>,
+    pub teams: HashMap<u8, Team>,
+    pub current: CurrentContext,
+    pub round: Round,
+}
+
+#[derive(Debug, Clone)]
+pub struct GameAction {
+    pub description: String,
+    pub snapshot: GameSnapshot,
+    /// Score corrections made by the action. The audit log is append-only,
+    /// so undo and redo log their reversal instead of dropping them
+    pub score_adjustments: Vec<ScoreAdjustment>,
+}
+
+/// Undo/redo stacks of host actions.
+///
+/// Undo stack keeps the state before the action, redo stack keeps the state the undo
+/// rolled back from.
+#[derive(Debug, Default)]
+pub struct GameHistory {
+    undo_stack: Vec<GameAction>,
+    redo_stack: Vec<GameAction>,
+}
+
+impl GameHistory {
+    pub fn record(&mut self, action: GameAction) {
+        self.undo_stack.push(action);
+        self.redo_stack.clear();
+    }
+
+    pub fn next_undo(&self) -> Option<&GameAction> {
+        self.undo_stack.last()
+    }
+
+    pub fn next_redo(&self) -> Option<&GameAction> {
+        self.redo_stack.last()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+}
+
+impl GameContext {
+    pub fn take_snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            players: self.players.clone(),
+            teams: self.teams.clone(),
+            current: self.current.clone(),
+            round: self.get_current_round().clone(),
+        }
+    }
+
+    /// Runs the action and records the state before it, so the action can be undone.
+    /// Failed actions are rolled back and not recorded
+    pub fn record_action<T>(
+        &mut self,
+        description: String,
+        action: impl FnOnce(&mut Self) -> Result<T, GameplayError>,
+    ) -> Result<T, GameplayError> {
+        let snapshot = self.take_snapshot();
+        let adjustments_before = self.score_adjustments.len();
+        let transitions_before = self.state_transitions.len();
+        let result = match action(self) {
+            Ok(result) => result,
+            Err(e) => {
+                log::info!("Action failed, rolling back: {}", description);
+                self.restore_snapshot(snapshot, None);
+                self.state_transitions.truncate(transitions_before);
+                return Err(e);
+            }
+        };
+        let scores_before = snapshot
+            .players
+            .iter()
+            .map(|(&id, p)| (id, p.stats.score))
+            .collect();
+        self.journal_score_changes(&scores_before);
+
+        log::info!("Action recorded: {}", description);
+        self.history.record(GameAction {
+            description,
+            snapshot,
+            score_adjustments: self.score_adjustments[adjustments_before..].to_vec(),
+        });
+        Ok(result)
+    }
+
+    pub fn undo_last_action(&mut self) -> Result<String, GameplayError> {
+        self.ensure_command_allowed(GameCommand::Undo)?;
+        let action = self
+            .history
+            .undo_stack
+            .pop()
+            .ok_or(Report::new(GameplayError::OperationForbidden))
+            .attach_printable("Nothing to undo")?;
+
+        log::info!("Undoing: {}", action.description);
+        self.history.redo_stack.push(GameAction {
+            description: action.description.clone(),
+            snapshot: self.take_snapshot(),
+            score_adjustments: action.score_adjustments.clone(),
+        });
+        self.journal.record(JournalEvent::ActionUndone {
+            description: action.description.clone(),
+        });
+        self.restore_snapshot(action.snapshot, Some(GameCommand::Undo));
+        self.log_undone_score_adjustments(&action.score_adjustments);
+        Ok(action.description)
+    }
+
+    pub fn redo_last_action(&mut self) -> Result<String, GameplayError> {
+        self.ensure_command_allowed(GameCommand::Redo)?;
+        let action = self
+            .history
+            .redo_stack
+            .pop()
+            .ok_or(Report::new(GameplayError::OperationForbidden))
+            .attach_printable("Nothing to redo")?;
+
+        log::info!("Redoing: {}", action.description);
+        self.history.undo_stack.push(GameAction {
+            description: action.description.clone(),
+            snapshot: self.take_snapshot(),
+            score_adjustments: action.score_adjustments.clone(),
+        });
+        self.journal.record(JournalEvent::ActionRedone {
+            description: action.description.clone(),
+        });
+        self.restore_snapshot(action.snapshot, Some(GameCommand::Redo));
+        self.log_redone_score_adjustments(&action.score_adjustments);
+        Ok(action.description)
+    }
+
+    /// Restores the snapshot. Clocks keep running: round time is not rolled back and
+    /// the answer countdown is stopped.
+    /// Without the command the snapshot is a rollback of the failed action: changes it made
+    /// were never journaled, so neither is their rollback
+    /// Undo and redo stop the countdown, a rolled back action keeps it running
+    fn restore_snapshot(&mut self, snapshot: GameSnapshot, command: Option<GameCommand>) {
+        if command.is_some() {
+            self.cancel_answer_timer();
+        }
+        let scores_before = self.player_scores();
+        let state_before = self.current.game_state().clone();
+
+        let round_timer = std::mem::take(&mut self.current.round_timer);
+        let answer_attempt = self.current.answer_attempt;
+
+        self.players = snapshot.players;
+        self.teams = snapshot.teams;
+        self.current = snapshot.current;
+        self.current.round_timer = round_timer;
+        self.current.answer_attempt = answer_attempt;
+        if command.is_some() {
+            self.current.answer_timer = None;
+        }
+
+        let state = self.current.game_state().clone();
+        self.current
+            .round_timer
+            .set_paused_by_game(state != GameState::QuestionChoosing);
+        if let Some(command) = command {
+            self.record_transition(state_before, state, command);
+        }
+
+        let round_index = self.current.round_index;
+        match self.game_pack.content.rounds.get_mut(round_index) {
+            Some(round) => *round = snapshot.round,
+            None => log::error!("Can't restore round #{}: not present in pack", round_index),
+        }
+        if command.is_some() {
+            self.journal_score_changes(&scores_before);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::answer_timer::AnswerTimer;
+    use std::time::Duration;
+
+    #[test]
+    fn test_undo_restores_scores_and_new_action_drops_redo() {
+        let mut ctx = GameContext::default();
+        ctx.game_pack.content.rounds.push(Round::default());
+        ctx.players.insert(1, Player::default());
+        ctx.current.set_game_state(GameState::QuestionChoosing);
+
+        let add_score = |ctx: &mut GameContext| -> Result<(), GameplayError> {
+            ctx.players.get_mut(&1).expect("Test").stats.score += 100;
+            Ok(())
+        };
+        ctx.record_action("first".to_string(), add_score)
+            .expect("Test");
+        ctx.record_action("second".to_string(), add_score)
+            .expect("Test");
+
+        assert_eq!(ctx.undo_last_action().expect("Test"), "second");
+        assert_eq!(ctx.players[&1].stats.score, 100);
+        assert_eq!(ctx.history.next_redo().expect("Test").description, "second");
+
+        ctx.record_action("third".to_string(), add_score)
+            .expect("Test");
+        assert!(ctx.history.next_redo().is_none());
+        assert!(ctx.redo_last_action().is_err());
+        assert_eq!(ctx.players[&1].stats.score, 200);
+    }
+
+    #[test]
+    fn test_failed_action_is_rolled_back() {
+        let mut ctx = GameContext::default();
+        ctx.game_pack.content.rounds.push(Round::default());
+        ctx.players.insert(1, Player::default());
+        ctx.current.set_game_state(GameState::QuestionChoosing);
+        ctx.current.answer_timer = Some(AnswerTimer::start(1, 1, Duration::from_secs(10)));
+
+        let result = ctx.record_action("broken".to_string(), |ctx| -> Result<(), GameplayError> {
+            ctx.cancel_answer_timer();
+            ctx.players.get_mut(&1).expect("Test").stats.score += 100;
+            ctx.current.set_game_state(GameState::AnswerAllowed);
+            Err(Report::new(GameplayError::OperationForbidden))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(ctx.players[&1].stats.score, 0);
+        assert_eq!(*ctx.current.game_state(), GameState::QuestionChoosing);
+        assert!(ctx.history.next_undo().is_none());
+        assert_eq!(
+            ctx.current.answer_timer.as_ref().map(|t| t.attempt),
+            Some(1)
+        );
+    }
+}
@@ -0,0 +1,591 @@
+use error_stack::{IntoReport, Report, Result, ResultExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+use crate::core::auction::AuctionBidType;
+use crate::core::false_start::FalseStartPenalty;
+use crate::core::game_entities::{GameContext, Player};
+use crate::core::rule_set::RuleSetPreset;
+use crate::core::score_adjustment::ScoreAdjustmentType;
+use crate::core::team::Team;
+use crate::core::tie_break::TieBreakSource;
+use crate::game_pack::game_pack_entites::GamePack;
+use crate::game_pack::game_pack_loader::load_game_pack;
+use crate::game_pack::pack_content_entities::QuestionId;
+use crate::hub_comm::hw::internal::api_types::TermEvent;
+
+const JOURNAL_DIR_NAME: &str = "journal";
+
+#[derive(Debug, Clone, Serialize, Error)]
+pub enum GameJournalError {
+    #[error("Journal can't be read")]
+    JournalNotReadable,
+    #[error("Journal entry is corrupted")]
+    CorruptedEntry,
+    #[error("Journal doesn't start with the game start")]
+    GameStartMissing,
+    #[error("Pack of the journal can't be loaded")]
+    PackNotAvailable,
+    #[error("Pack doesn't match the journal")]
+    PackMismatch,
+    #[error("Replay diverged from the journal")]
+    ReplayDiverged,
+}
+
+/// Everything that happens in the game.
+///
+/// Events the host triggers are enough to rebuild the game. Raw terminal events and score
+/// changes are kept for analysis: replay skips the former and checks the latter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum JournalEvent {
+    GameStarted {
+        pack_path: String,
+        pack_hash: String,
+        players: Vec<Player>,
+        round_duration_sec: Option<u64>,
+        answer_window_sec: Option<u64>,
+        #[serde(default)]
+        false_start_penalty: FalseStartPenalty,
+        #[serde(default)]
+        rule_set: RuleSetPreset,
+        #[serde(default)]
+        teams: Vec<Team>,
+    },
+    /// Saved game is resumed after a crash: the game goes on from the saved state
+    GameResumed,
+    ChooserAssigned {
+        player_id: u8,
+    },
+    QuestionSelected {
+        id: QuestionId,
+    },
+    PigInPokeVictimSelected {
+        player_id: u8,
+    },
+    AuctionBidPlaced {
+        player_id: u8,
+        bid_type: AuctionBidType,
+        amount: i32,
+    },
+    AnswerAllowed {
+        base_timestamp: u32,
+    },
+    TermEventReceived {
+        event: TermEvent,
+    },
+    FalseStart {
+        player_id: u8,
+        /// Teammate who clicked for the team
+        #[serde(default)]
+        term_id: Option<u8>,
+        #[serde(default)]
+        timestamp: u32,
+    },
+    FastestClick {
+        player_id: u8,
+        /// Teammate who clicked for the team
+        #[serde(default)]
+        term_id: Option<u8>,
+    },
+    AnswerJudged {
+        player_id: u8,
+        answered_correctly: bool,
+    },
+    QuestionFinishedPrematurely,
+    ActionUndone {
+        description: String,
+    },
+    ActionRedone {
+        description: String,
+    },
+    ScoreAdjusted {
+        player_id: u8,
+        adjustment_type: ScoreAdjustmentType,
+        amount: i32,
+        reason: String,
+    },
+    ScoreChanged {
+        player_id: u8,
+        score_before: i32,
+        score_after: i32,
+    },
+    RoundStarted {
+        round_index: usize,
+    },
+    FinalThemeEliminated {
+        player_id: u8,
+        theme_index: usize,
+    },
+    FinalWagerPlaced {
+        player_id: u8,
+        wager: i32,
+    },
+    FinalQuestionShown,
+    FinalAnswerSubmitted {
+        player_id: u8,
+        answer: String,
+    },
+    FinalAnswerJudged {
+        player_id: u8,
+        answered_correctly: bool,
+    },
+    TieBreakStarted {
+        source: TieBreakSource,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    #[serde(flatten)]
+    pub event: JournalEvent,
+}
+
+/// Append-only game journal. Entries are kept in memory and, for games with a data dir,
+/// appended to the JSON lines file in its `journal` subdir
+#[derive(Debug, Default)]
+pub struct GameJournal {
+    entries: Vec<JournalEntry>,
+    file_path: Option<PathBuf>,
+    file: Option<File>,
+}
+
+impl GameJournal {
+    /// Starts a new journal file in the data dir, if any. Entries of the previous game are
+    /// dropped
+    pub fn start_file(&mut self, data_dir: Option<&Path>) -> Result<(), GameJournalError> {
+        self.entries.clear();
+        self.file = None;
+        self.file_path = None;
+
+        let Some(data_dir) = data_dir else {
+            return Ok(());
+        };
+        let path = journal_file_path(data_dir, now_ms())?;
+        let file = File::create(&path)
+            .into_report()
+            .change_context(GameJournalError::JournalNotReadable)
+            .attach_printable(format!("Can't create journal file {:?}", path))?;
+
+        log::info!("Game journal: {:?}", path);
+        self.file = Some(file);
+        self.file_path = Some(path);
+        Ok(())
+    }
+
+    /// Appends to the journal file of the resumed game. Its entries are read back first
+    pub fn continue_file(&mut self, path: &Path) -> Result<(), GameJournalError> {
+        let entries = read_journal(path)?;
+        let file = OpenOptions::new()
+            .append(true)
+            .open(path)
+            .into_report()
+            .change_context(GameJournalError::JournalNotReadable)
+            .attach_printable(format!("Can't open journal file {:?}", path))?;
+
+        log::info!("Game journal continued: {:?}", path);
+        self.entries = entries;
+        self.file = Some(file);
+        self.file_path = Some(path.to_path_buf());
+        Ok(())
+    }
+
+    pub fn record(&mut self, event: JournalEvent) {
+        let entry = JournalEntry {
+            timestamp_ms: now_ms(),
+            event,
+        };
+        log::debug!("Journal: {:?}", entry);
+
+        if let Some(file) = self.file.as_mut() {
+            let written = serde_json::to_string(&entry)
+                .map_err(|e| e.to_string())
+                .and_then(|line| writeln!(file, "{}", line).map_err(|e| e.to_string()));
+            if let Err(e) = written {
+                log::error!("Can't write journal entry: {}", e);
+            }
+        }
+        self.entries.push(entry);
+    }
+
+    pub fn entries(&self) -> &Vec<JournalEntry> {
+        &self.entries
+    }
+
+    pub fn file_path(&self) -> Option<&PathBuf> {
+        self.file_path.as_ref()
+    }
+}
+
+impl GameContext {
+    pub(crate) fn player_scores(&self) -> HashMap<u8, i32> {
+        self.players
+            .iter()
+            .map(|(&id, p)| (id, p.stats.score))
+            .collect()
+    }
+
+    /// Records score changes made since `scores_before` was taken
+    pub(crate) fn journal_score_changes(&mut self, scores_before: &HashMap<u8, i32>) {
+        let mut changes: Vec<(u8, i32, i32)> = self
+            .players
+            .iter()
+            .filter_map(|(&id, p)| {
+                let score_before = scores_before.get(&id).copied().unwrap_or_default();
+                (score_before != p.stats.score).then_some((id, score_before, p.stats.score))
+            })
+            .collect();
+        changes.sort();
+
+        for (player_id, score_before, score_after) in changes {
+            self.journal.record(JournalEvent::ScoreChanged {
+                player_id,
+                score_before,
+                score_after,
+            });
+        }
+    }
+
+    /// Rebuilds the game from the journal on top of the pack the game was played with.
+    /// The hub is not involved: terminal events are taken from the recorded decisions
+    pub fn replay_journal(
+        entries: &[JournalEntry],
+        pack: GamePack,
+    ) -> Result<GameContext, GameJournalError> {
+        let mut ctx = GameContext::default();
+        ctx.game_pack = pack;
+
+        match entries.first().map(|e| &e.event) {
+            Some(JournalEvent::GameStarted { .. }) => {}
+            _ => return Err(Report::new(GameJournalError::GameStartMissing)),
+        }
+
+        for (index, entry) in entries.iter().enumerate() {
+            ctx.apply_journal_event(&entry.event)
+                .attach_printable(format!("Replay failed at entry #{}: {:?}", index, entry))?;
+        }
+        Ok(ctx)
+    }
+
+    fn apply_journal_event(&mut self, event: &JournalEvent) -> Result<(), GameJournalError> {
+        let diverged = |e: Report<_>| e.change_context(GameJournalError::ReplayDiverged);
+
+        match event.clone() {
+            JournalEvent::GameStarted {
+                pack_hash,
+                players,
+                round_duration_sec,
+                answer_window_sec,
+                false_start_penalty,
+                rule_set,
+                teams,
+                ..
+            } => {
+                if pack_hash != self.game_pack.archive_hash {
+                    return Err(Report::new(GameJournalError::PackMismatch)).attach_printable(
+                        format!(
+                            "Journal expects pack hash {}, got {}",
+                            pack_hash, self.game_pack.archive_hash
+                        ),
+                    );
+                }
+                self.players = players.into_iter().map(|p| (p.term_id, p)).collect();
+                self.round_duration = round_duration_sec.map(Duration::from_secs);
+                self.answer_window = answer_window_sec.map(Duration::from_secs);
+                self.false_start_penalty = false_start_penalty;
+                self.rule_set = rule_set;
+                self.teams = teams.into_iter().map(|t| (t.id, t)).collect();
+                self.begin_game().map_err(diverged)?;
+                self.journal.record(event.clone());
+            }
+            JournalEvent::GameResumed => {
+                self.resume_saved_state()
+                    .change_context(GameJournalError::ReplayDiverged)?;
+                self.journal.record(event.clone());
+            }
+            JournalEvent::ChooserAssigned { player_id } => {
+                self.assign_first_chooser(player_id).map_err(diverged)?;
+            }
+            JournalEvent::QuestionSelected { id } => {
+                self.get_pack_question(id).map_err(diverged)?;
+            }
+            JournalEvent::PigInPokeVictimSelected { player_id } => {
+                self.set_pip_victim(player_id).map_err(diverged)?;
+            }
+            JournalEvent::AuctionBidPlaced {
+                player_id,
+                bid_type,
+                amount,
+            } => {
+                self.place_auction_bid(player_id, bid_type, amount)
+                    .map_err(diverged)?;
+            }
+            JournalEvent::AnswerAllowed { base_timestamp } => {
+                self.allow_answer_from(base_timestamp).map_err(diverged)?;
+            }
+            JournalEvent::TermEventReceived { .. } => {}
+            JournalEvent::FalseStart {
+                player_id,
+                term_id,
+                timestamp,
+            } => {
+                self.register_false_start(player_id, term_id, timestamp);
+            }
+            JournalEvent::FastestClick { player_id, term_id } => {
+                self.current.answering_term_id = term_id;
+                self.accept_fastest_click(player_id).map_err(diverged)?;
+            }
+            JournalEvent::AnswerJudged {
+                answered_correctly, ..
+            } => {
+                self.answer_question(answered_correctly).map_err(diverged)?;
+            }
+            JournalEvent::QuestionFinishedPrematurely => {
+                self.finish_question_prematurely().map_err(diverged)?;
+            }
+            JournalEvent::ActionUndone { .. } => {
+                self.undo_last_action().map_err(diverged)?;
+            }
+            JournalEvent::ActionRedone { .. } => {
+                self.redo_last_action().map_err(diverged)?;
+            }
+            JournalEvent::ScoreAdjusted {
+                player_id,
+                adjustment_type,
+                amount,
+                reason,
+            } => {
+                self.adjust_player_score(player_id, adjustment_type, amount, reason)
+                    .map_err(diverged)?;
+            }
+            JournalEvent::ScoreChanged {
+                player_id,
+                score_after,
+                ..
+            } => {
+                let score = self.player_scores().get(&player_id).copied();
+                if score != Some(score_after) {
+                    return Err(Report::new(GameJournalError::ReplayDiverged)).attach_printable(
+                        format!(
+                            "Player {} score is {:?}, journal expects {}",
+                            player_id, score, score_after
+                        ),
+                    );
+                }
+            }
+            JournalEvent::RoundStarted { round_index } => {
+                self.init_next_round().map_err(diverged)?;
+                if self.current.round_index != round_index {
+                    return Err(Report::new(GameJournalError::ReplayDiverged)).attach_printable(
+                        format!(
+                            "Round #{} started instead of #{}",
+                            self.current.round_index, round_index
+                        ),
+                    );
+                }
+            }
+            JournalEvent::FinalThemeEliminated {
+                player_id,
+                theme_index,
+            } => {
+                self.eliminate_final_theme(player_id, theme_index)
+                    .map_err(diverged)?;
+            }
+            JournalEvent::FinalWagerPlaced { player_id, wager } => {
+                self.place_final_wager(player_id, wager).map_err(diverged)?;
+            }
+            JournalEvent::FinalQuestionShown => {
+                self.get_final_question().map_err(diverged)?;
+            }
+            JournalEvent::FinalAnswerSubmitted { player_id, answer } => {
+                self.submit_final_answer(player_id, answer)
+                    .map_err(diverged)?;
+            }
+            JournalEvent::FinalAnswerJudged {
+                player_id,
+                answered_correctly,
+            } => {
+                self.judge_final_answer(player_id, answered_correctly)
+                    .map_err(diverged)?;
+            }
+            JournalEvent::TieBreakStarted { source } => {
+                self.start_tie_break(source).map_err(diverged)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn journal_game_start(&mut self) {
+        if let Err(e) = self.journal.start_file(self.data_dir.as_deref()) {
+            log::error!("Game journal is kept in memory only: {:?}", e);
+        }
+
+        self.journal.record(JournalEvent::GameStarted {
+            pack_path: self.game_pack.archive_path.clone(),
+            pack_hash: self.game_pack.archive_hash.clone(),
+            players: self.players.values().cloned().collect(),
+            round_duration_sec: self.round_duration.map(|d| d.as_secs()),
+            answer_window_sec: self.answer_window.map(|d| d.as_secs()),
+            false_start_penalty: self.false_start_penalty,
+            rule_set: self.rule_set,
+            teams: self.fetch_teams(),
+        });
+    }
+
+    /// Goes on with the journal of the saved game, so it covers the game before the crash too
+    pub(crate) fn journal_game_resume(&mut self, journal_path: Option<&Path>) {
+        match journal_path {
+            Some(path) => {
+                if let Err(e) = self.journal.continue_file(path) {
+                    log::error!("Game journal is kept in memory only: {:?}", e);
+                }
+            }
+            None => log::error!("Saved game has no journal. Game journal is kept in memory only"),
+        }
+        self.journal.record(JournalEvent::GameResumed);
+    }
+}
+
+/// Reads the journal file, loads the pack the game was played with and replays the game
+pub fn replay_journal_file(path: &Path) -> Result<GameContext, GameJournalError> {
+    let entries = read_journal(path)?;
+    let Some(JournalEvent::GameStarted { pack_path, .. }) = entries.first().map(|e| &e.event)
+    else {
+        return Err(Report::new(GameJournalError::GameStartMissing))
+            .attach_printable(format!("Journal {:?} has no game start", path));
+    };
+
+    // The journaled game was already played with this pack
+    let pack = load_game_pack(pack_path, true).map_err(|e| {
+        Report::new(GameJournalError::PackNotAvailable)
+            .attach_printable(format!("Can't load pack {}: {:?}", pack_path, e))
+    })?;
+
+    GameContext::replay_journal(&entries, pack)
+}
+
+pub fn read_journal(path: &Path) -> Result<Vec<JournalEntry>, GameJournalError> {
+    let content = fs::read_to_string(path)
+        .into_report()
+        .change_context(GameJournalError::JournalNotReadable)
+        .attach_printable(format!("Can't read journal {:?}", path))?;
+
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line)
+                .into_report()
+                .change_context(GameJournalError::CorruptedEntry)
+                .attach_printable(format!("Can't parse line {} of {:?}", index + 1, path))
+        })
+        .collect()
+}
+
+fn journal_file_path(data_dir: &Path, start_ms: u64) -> Result<PathBuf, GameJournalError> {
+    let journal_dir = data_dir.join(JOURNAL_DIR_NAME);
+    fs::create_dir_all(&journal_dir)
+        .into_report()
+        .change_context(GameJournalError::JournalNotReadable)
+        .attach_printable(format!("Can't create journal dir: {:?}", journal_dir))?;
+
+    Ok(journal_dir.join(format!("game_{}.jsonl", start_ms)))
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_pack::pack_content_entities::test_pack::{make_pack, make_round, make_theme};
+
+    fn music_pack() -> GamePack {
+        make_pack(vec![make_round(vec![make_theme("Music", &[100, 200])])])
+    }
+
+    #[test]
+    fn test_game_is_rebuilt_from_journal() {
+        let mut ctx = GameContext::default();
+        ctx.game_pack = music_pack();
+        for id in [1, 2] {
+            let player = Player {
+                term_id: id,
+                ..Default::default()
+            };
+            ctx.players.insert(id, player);
+        }
+
+        ctx.begin_game().expect("Test");
+        ctx.journal.record(JournalEvent::GameStarted {
+            pack_path: String::default(),
+            pack_hash: "42".to_string(),
+            players: ctx.players.values().cloned().collect(),
+            round_duration_sec: None,
+            answer_window_sec: None,
+            false_start_penalty: FalseStartPenalty::default(),
+            rule_set: RuleSetPreset::default(),
+            teams: vec![],
+        });
+        ctx.assign_first_chooser(1).expect("Test");
+        let missing = QuestionId {
+            question: 5,
+            ..Default::default()
+        };
+        assert!(ctx.get_pack_question(missing).is_err());
+        let id = QuestionId {
+            question: 1,
+            ..Default::default()
+        };
+        ctx.get_pack_question(id).expect("Test");
+        ctx.allow_answer_from(0).expect("Test");
+        ctx.register_false_start(1, None, 0);
+        ctx.accept_fastest_click(2).expect("Test");
+        ctx.answer_question(true).expect("Test");
+
+        ctx.resume_saved_state().expect("Test");
+        ctx.journal_game_resume(None);
+        ctx.get_pack_question(QuestionId::default()).expect("Test");
+        ctx.finish_question_prematurely().expect("Test");
+
+        let selections = ctx
+            .journal
+            .entries()
+            .iter()
+            .filter(|e| matches!(e.event, JournalEvent::QuestionSelected { .. }))
+            .count();
+        assert_eq!(selections, 2);
+
+        let json: Vec<String> = ctx
+            .journal
+            .entries()
+            .iter()
+            .map(|e| serde_json::to_string(e).expect("Test"))
+            .collect();
+        let entries: Vec<JournalEntry> = json
+            .iter()
+            .map(|line| serde_json::from_str(line).expect("Test"))
+            .collect();
+
+        let replayed = GameContext::replay_journal(&entries, music_pack()).expect("Test");
+        assert_eq!(replayed.player_scores(), ctx.player_scores());
+        assert_eq!(replayed.players[&2].stats.score, 200);
+        assert_eq!(replayed.players[&1].stats.false_start_num, 1);
+        assert_eq!(replayed.get_current_round().questions_left, 0);
+
+        assert!(GameContext::replay_journal(&entries[1..], music_pack()).is_err());
+    }
+}
@@ -2,33 +2,53 @@ use crate::hub_comm::common::hub_api::HubManager;
 use error_stack::{IntoReport, Report, Result, ResultExt};
 use std::collections::HashMap;
 use std::sync::atomic::{Ordering};
-use std::sync::mpsc::{Receiver, Sender};
+use std::sync::mpsc::Sender;
 use std::sync::{mpsc, Arc, RwLock, RwLockReadGuard};
 use std::thread;
 use std::thread::{sleep, JoinHandle};
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
-use crate::api::dto::{PlayerStatsDto, RoundStatsDto};
+use crate::api::dto::QuestionType;
+use crate::core::game_journal::JournalEvent;
+use crate::core::game_state_machine::GameCommand;
+use crate::core::round_timer::RoundTimer;
 use crate::core::game_entities::{
     GameContext, GamePackError, GameState, GameplayError, Player, PlayerState,
 };
 
-use crate::game_pack::pack_content_entities::{Question, Round};
-use crate::hub_comm::hw::hw_hub_manager::{get_epoch_ms, HubManagerError};
+use crate::game_pack::pack_content_entities::{
+    Atom, HostNotes, PigInPokeSelf, Question, QuestionId, Round, Theme,
+};
+use crate::hub_comm::hw::hw_hub_manager::get_epoch_ms;
 use crate::hub_comm::hw::internal::api_types::TermButtonState::Pressed;
 use crate::hub_comm::hw::internal::api_types::TermEvent;
 
-const EVT_POLLING_INTERVAL_MS: u64 = 1000;
+const EVT_POLLING_INTERVAL_MS: u64 = 100;
 
 impl GameContext {
     pub fn start_the_game(&mut self) -> Result<(), GameplayError> {
-        self.update_game_state(GameState::QuestionChoosing);
-
         if self.players.len() < 2 {
             log::info!("Not enough players to run the game.");
             return Err(GameplayError::PlayerNotPresent).into_report();
         }
 
+        self.begin_game()?;
+        self.journal_game_start();
+
+        self.connect_hub_events();
+
+        // Everyone starts with zero, so the lowest id opens the game
+        let chooser_id = self
+            .lowest_score_player_id()
+            .ok_or(GameplayError::PlayerNotPresent)
+            .into_report()
+            .attach_printable("Can't find any player to choose the first question")?;
+
+        self.assign_first_chooser(chooser_id)
+    }
+
+    /// Starts reading the hub events into the event queue
+    pub(crate) fn connect_hub_events(&mut self) {
         let (event_tx, event_rx) = mpsc::channel();
         self.event_queue = Some(event_rx);
 
@@ -36,32 +56,24 @@ impl GameContext {
             self.get_hub_ref().clone(),
             event_tx
         );
+    }
 
-        let ts = get_epoch_ms().expect("No epoch today");
-        self.allow_answer_timestamp.swap(ts, Ordering::Relaxed);
-
-        let q_picker_id = match self.get_fastest_click_player_id() {
-            Ok(id) => id,
-            Err(err) => {
-                log::error!("{:#?}", err);
-
-                self.players
-                    .values()
-                    .next()
-                    .ok_or(GameplayError::PlayerNotPresent)
-                    .into_report()
-                    .attach_printable("Can't find any player to play with :D")?
-                    .term_id
-            }
-        };
+    pub(crate) fn begin_game(&mut self) -> Result<(), GameplayError> {
+        self.current.round_timer = RoundTimer::start(self.round_duration);
+        self.history.clear();
+        self.score_adjustments.clear();
+        self.state_transitions.clear();
+        self.update_game_state(GameState::QuestionChoosing, GameCommand::StartGame)
+    }
 
-        self.current.set_active_player_id(q_picker_id);
-        let player = self
-            .players
-            .get_mut(&self.current.active_player_id())
-            .ok_or(GameplayError::PlayerNotPresent)
-            .into_report()?;
-        player.state = PlayerState::QuestionChooser;
+    pub(crate) fn assign_first_chooser(&mut self, player_id: u8) -> Result<(), GameplayError> {
+        if !self.players.contains_key(&player_id) {
+            return Err(Report::new(GameplayError::PlayerNotPresent))
+                .attach_printable(format!("Can't find chooser with id {}", player_id));
+        }
+        self.current.set_active_player_id(player_id);
+        self.set_question_chooser(player_id);
+        self.journal.record(JournalEvent::ChooserAssigned { player_id });
         Ok(())
     }
 
@@ -70,81 +82,205 @@ impl GameContext {
         &self.players
     }
 
-    pub fn get_pack_question(
-        &mut self,
-        theme: &String,
-        price: &i32,
-    ) -> Result<(Question, i32), GameplayError> {
-        log::info!("Get question from category: {theme}, price: {price}");
+    pub fn get_pack_question(&mut self, id: QuestionId) -> Result<(Question, i32), GameplayError> {
+        log::info!("Get question {id}");
+        self.ensure_command_allowed(GameCommand::SelectQuestion)?;
+        if self.current.round_timer.is_expired() {
+            return Err(Report::new(GameplayError::OperationForbidden))
+                .attach_printable("Round time is over. Start the next round");
+        }
 
+        let chooser_id = self.current.question_chooser_id;
         self.current.set_active_player_id(0);
         self.update_non_target_player_states();
 
-        // if *self.current.game_state() != GameState::QuestionChoosing {
-        //     return Err(Report::new(GamePackError::QuestionNotPresent)
-        //         .change_context(GameplayError::PackElementNotPresent));
-        // }
-
         let (question, question_number) = self
-            .get_question(theme, price)
+            .get_question(id)
             .change_context(GameplayError::PackElementNotPresent)?;
 
-        self.update_game_state(GameState::QuestionSelected);
+        if matches!(
+            question.question_type,
+            QuestionType::PigInPoke | QuestionType::Auction | QuestionType::Sponsored
+        ) {
+            log::info!("{:?} question. Player {chooser_id} keeps the turn", question.question_type);
+            self.current.set_active_player_id(chooser_id);
+            if let Some(chooser) = self.players.get_mut(&chooser_id) {
+                chooser.state = PlayerState::QuestionChooser;
+            }
+        }
+
+        if question.question_type == QuestionType::Auction {
+            self.start_auction(chooser_id);
+        }
+
+        self.update_game_state(GameState::QuestionSelected, GameCommand::SelectQuestion)?;
+        if question.question_type == QuestionType::Sponsored {
+            self.assign_question_to_player(chooser_id, GameCommand::SelectQuestion)?;
+        }
+        // Failed actions never happened, so the journal gets only the completed ones
+        self.journal.record(JournalEvent::QuestionSelected { id });
 
         Ok((question, question_number))
     }
 
-    pub fn remove_question(&mut self, theme: &String, price: &i32) -> Result<(), GamePackError> {
-        log::info!("Try to remove question from category: {theme}, price: {price}");
-        let round = self.get_current_round_mut();
-        let theme = round
+    pub fn set_pip_victim(&mut self, victim_id: u8) -> Result<(), GameplayError> {
+        self.ensure_command_allowed(GameCommand::PassPigInPoke)?;
+        if self.current.question_type != QuestionType::PigInPoke {
+            return Err(Report::new(GameplayError::OperationForbidden)).attach_printable(format!(
+                "Can't pass {:?} question to the victim",
+                self.current.question_type
+            ));
+        }
+
+        let chooser_id = self.current.active_player_id();
+        let id = self.current.question_id;
+        let self_pass = self
+            .get_current_round()
             .themes
-            .get_mut(theme)
-            .ok_or(GamePackError::ThemeNotPresent)
-            .into_report()
-            .attach_printable(format!("Can't find theme: {theme:?}"))?;
+            .get(id.theme)
+            .and_then(|theme| theme.get_question(id.question))
+            .and_then(|question| question.pip_params.as_ref())
+            .map(|params| params.self_pass)
+            .unwrap_or_default();
+        match self_pass {
+            PigInPokeSelf::Forbidden if victim_id == chooser_id => {
+                return Err(Report::new(GameplayError::OperationForbidden))
+                    .attach_printable("Pig in poke can't be passed to the player who chose it");
+            }
+            PigInPokeSelf::Only if victim_id != chooser_id => {
+                return Err(Report::new(GameplayError::OperationForbidden))
+                    .attach_printable("Pig in poke stays with the player who chose it");
+            }
+            _ => {}
+        }
 
+        let victim = self
+            .players
+            .get(&victim_id)
+            .ok_or(Report::new(GameplayError::PlayerNotPresent))
+            .attach_printable(format!("Can't find player with id {}", victim_id))?;
+        if victim.state == PlayerState::Dead {
+            return Err(Report::new(GameplayError::OperationForbidden))
+                .attach_printable(format!("Player {} is out of the game", victim_id));
+        }
+
+        log::info!("Pig in poke goes from player {} to player {}", chooser_id, victim_id);
+        self.assign_question_to_player(victim_id, GameCommand::PassPigInPoke)?;
+        self.journal.record(JournalEvent::PigInPokeVictimSelected {
+            player_id: victim_id,
+        });
+        Ok(())
+    }
+
+    pub fn remove_question(&mut self, id: QuestionId) -> Result<(), GamePackError> {
+        log::info!("Try to remove question {id}");
+        let theme = self.get_pack_theme_mut(id)?;
         let _ = theme
-            .pop_question(price)
+            .pop_question(id.question)
             .ok_or(GamePackError::QuestionNotPresent)
             .into_report()
-            .attach_printable(format!(
-                "Can't find question with price {price:?} in theme: {theme:?}"
-            ))?;
+            .attach_printable(format!("Can't find question {id} in theme: {:?}", theme.name))?;
 
+        let round = self.get_current_round_mut();
         round.questions_left -= 1;
         log::info!("Question left: {}", round.questions_left);
         Ok(())
     }
 
     pub fn finish_question_prematurely(&mut self) -> Result<(), GameplayError> {
+        self.ensure_command_allowed(GameCommand::FinishQuestion)?;
+        let description = format!(
+            "Question {}:{} finished prematurely",
+            self.current.question_theme, self.current.question_nominal_price
+        );
+        self.record_action(description, |ctx| ctx.close_question_prematurely())
+    }
+
+    fn close_question_prematurely(&mut self) -> Result<(), GameplayError> {
+        self.cancel_answer_timer();
         self.current.answer_allowed = false;
         self.allow_answer_timestamp.swap(u32::MAX, Ordering::Relaxed);
 
         self.current.total_tries += 1;
         self.current.total_wrong_answers += 1;
 
-        let theme = self.current.question_theme.clone();
-        let price = self.current.question_price;
-        log::info!(">>> Trying to remove question from category: {theme}, price: {price}");
+        let id = self.current.question_id;
+        log::info!(">>> Trying to remove question {id}");
 
-        self.update_game_state(GameState::QuestionChoosing);
+        self.update_game_state(GameState::QuestionChoosing, GameCommand::FinishQuestion)?;
         self.update_non_target_player_states();
 
-        self.remove_question(&theme, &price)
+        self.remove_question(id)
             .change_context(GameplayError::PackElementNotPresent)?;
+        self.journal.record(JournalEvent::QuestionFinishedPrematurely);
         Ok(())
     }
 
+    /// Answer illustration of the last question. Hidden until the question is closed
+    pub fn get_answer_scene(&self) -> Result<&Vec<Atom>, GameplayError> {
+        let state = self.current.game_state();
+        if !matches!(
+            state,
+            GameState::QuestionChoosing | GameState::AnswerCorrect | GameState::NoPlayersToAnswerLeft
+        ) {
+            return Err(Report::new(GameplayError::OperationForbidden)).attach_printable(format!(
+                "Answer scene is hidden while the question is played: {:?}",
+                state
+            ));
+        }
+
+        Ok(&self.current.answer_scene)
+    }
+
+    /// Accepted and rejected answers with the comments of the current question
+    pub fn get_host_notes(&self) -> &HostNotes {
+        &self.current.host_notes
+    }
+
     pub fn has_next_question(&self) -> bool {
-        // self.current.has_next_question
-        let has_new_question = self.get_current_round().questions_left > 0;
+        let round_time_is_over = self.current.round_timer.is_expired();
+        if round_time_is_over {
+            log::info!("Round time is over");
+        }
+
+        let has_new_question =
+            self.get_current_round().questions_left > 0 && !round_time_is_over;
         log::info!("Has new question: {}", has_new_question);
         has_new_question
     }
 
-    pub fn allow_answer(&mut self) -> Result<(), HubManagerError> {
-        let timestamp = get_epoch_ms()?;
+    pub fn set_round_duration(&mut self, round_minutes: i32) {
+        self.round_duration = if round_minutes > 0 {
+            Some(Duration::from_secs(round_minutes as u64 * 60))
+        } else {
+            None
+        };
+        log::info!("Round duration set to: {:?}", self.round_duration);
+    }
+
+    pub fn set_answer_window(&mut self, answer_seconds: i32) {
+        self.answer_window = if answer_seconds > 0 {
+            Some(Duration::from_secs(answer_seconds as u64))
+        } else {
+            None
+        };
+        log::info!("Answer window set to: {:?}", self.answer_window);
+    }
+
+    pub fn set_round_timer_paused(&mut self, paused: bool) {
+        log::info!("Host sets round timer paused: {}", paused);
+        self.current.round_timer.set_paused_by_host(paused);
+    }
+
+    pub fn allow_answer(&mut self) -> Result<(), GameplayError> {
+        let timestamp = get_epoch_ms().change_context(GameplayError::InternalError)?;
+        self.allow_answer_from(timestamp)
+    }
+
+    /// Allows clicks made after the base timestamp
+    pub(crate) fn allow_answer_from(&mut self, timestamp: u32) -> Result<(), GameplayError> {
+        self.ensure_command_allowed(GameCommand::AllowAnswer)?;
+        self.update_game_state(GameState::AnswerAllowed, GameCommand::AllowAnswer)?;
         self.allow_answer_timestamp
             .swap(timestamp, Ordering::Relaxed);
         log::info!("Current answer base timestamp: {timestamp}");
@@ -152,37 +288,33 @@ impl GameContext {
         self.current.set_active_player_id(0);
         self.update_non_target_player_states();
         self.current.click_for_answer_allowed = true;
+        self.journal.record(JournalEvent::AnswerAllowed {
+            base_timestamp: timestamp,
+        });
         Ok(())
     }
 
-    pub fn get_fastest_click_player_id(&mut self) -> Result<u8, GameplayError> {
-        let players_allowed_to_click_num = self
-            .players
-            .values()
-            .filter(|&p| p.allowed_to_click())
-            .count();
-        if players_allowed_to_click_num == 0 {
-            let report = Report::new(GameplayError::OperationForbidden)
-                .attach_printable("Can't get first click: No players allowed to click left.");
-            return Err(report);
-        }
-
-        let fastest_player_id = self
-            .get_fastest_click_from_hub()
-            .change_context(GameplayError::HubOperationError)?;
-
+    /// Gives the right to answer to the player who clicked first
+    pub(crate) fn accept_fastest_click(&mut self, fastest_player_id: u8) -> Result<(), GameplayError> {
+        self.ensure_command_allowed(GameCommand::GetFastestClick)?;
         log::info!("Fastest click from user: {}", fastest_player_id);
-        self.current.click_for_answer_allowed = false;
-        self.current.answer_allowed = true;
-        self.current.set_active_player_id(fastest_player_id);
-
         self.players
             .get_mut(&fastest_player_id)
             .ok_or(Report::new(GameplayError::PlayerNotPresent))
             .attach_printable(format!("Can't find player with id {}", fastest_player_id))?
             .state = PlayerState::FirstResponse;
 
-        Ok(fastest_player_id)
+        self.current.click_for_answer_allowed = false;
+        self.current.answer_allowed = true;
+        self.current.set_active_player_id(fastest_player_id);
+        self.update_game_state(GameState::AnswerRequested, GameCommand::GetFastestClick)?;
+        self.journal.record(JournalEvent::FastestClick {
+            player_id: fastest_player_id,
+            term_id: self.current.answering_term_id,
+        });
+
+        self.start_answer_timer(fastest_player_id);
+        Ok(())
     }
 
     pub fn get_active_player_id(&self) -> u8 {
@@ -190,10 +322,23 @@ impl GameContext {
     }
 
     pub fn answer_question(&mut self, answered_correctly: bool) -> Result<bool, GameplayError> {
+        self.ensure_command_allowed(GameCommand::JudgeAnswer)?;
+        let description = format!(
+            "Player {} answered {} on {}:{}",
+            self.get_active_player_id(),
+            if answered_correctly { "correctly" } else { "wrong" },
+            self.current.question_theme,
+            self.current.question_nominal_price
+        );
+        self.record_action(description, |ctx| ctx.judge_answer(answered_correctly))
+    }
+
+    fn judge_answer(&mut self, answered_correctly: bool) -> Result<bool, GameplayError> {
         if !self.current.answer_allowed {
             return Err(Report::new(GameplayError::AnswerForbidden));
         }
 
+        self.cancel_answer_timer();
         self.current.answer_allowed = false;
         self.allow_answer_timestamp.swap(u32::MAX, Ordering::Relaxed);
 
@@ -204,6 +349,17 @@ impl GameContext {
             self.get_player_keys()
         );
 
+        let rules = self.rules();
+        let score_change = if answered_correctly {
+            rules.correct_answer_score(self.current.question_price)
+        } else if self.current.question_type == QuestionType::Sponsored {
+            // Sponsored questions cost nothing when answered wrong
+            0
+        } else {
+            rules.wrong_answer_score(self.current.question_price)
+        };
+        self.record_member_answer(answered_correctly, score_change);
+
         let response_player = {
             let active_player = self
                 .players
@@ -212,14 +368,15 @@ impl GameContext {
             if answered_correctly {
                 active_player.stats.correct_num += 1;
                 self.current.total_correct_answers += 1;
-                active_player.stats.score += self.current.question_price;
+                active_player.stats.score += score_change;
                 active_player.state = PlayerState::AnsweredCorrectly;
             } else {
                 active_player.stats.wrong_num += 1;
-                active_player.stats.score -= self.current.question_price;
+                active_player.stats.score += score_change;
                 active_player.state = PlayerState::AnsweredWrong;
             }
             self.current.total_tries += 1;
+            self.current.question_tries += 1;
             active_player.stats.total_tries += 1;
             active_player.clone()
         };
@@ -231,21 +388,35 @@ impl GameContext {
             self.current.total_wrong_answers += 1;
         }
 
-        let theme = self.current.question_theme.clone();
-        let price = self.current.question_price;
+        let id = self.current.question_id;
+
+        let command = GameCommand::JudgeAnswer;
+        if answered_correctly {
+            self.set_question_chooser(active_player_id);
+            self.update_game_state(GameState::AnswerCorrect, command)?;
+        } else {
+            self.update_game_state(GameState::AnswerWrong, command)?;
+            if self.no_players_to_answer_left() {
+                self.update_game_state(GameState::NoPlayersToAnswerLeft, command)?;
+            }
+        }
 
         let mut retry = true;
         if answered_correctly || self.no_players_to_answer_left() {
             log::info!("Removing question from the pack");
 
             retry = false;
-            self.update_game_state(GameState::QuestionChoosing);
+            self.update_game_state(GameState::QuestionChoosing, command)?;
             self.update_non_target_player_states();
 
-            self.remove_question(&theme, &price)
+            self.remove_question(id)
                 .change_context(GameplayError::PackElementNotPresent)?;
         }
 
+        self.journal.record(JournalEvent::AnswerJudged {
+            player_id: active_player_id,
+            answered_correctly,
+        });
         Ok(retry)
     }
 
@@ -259,21 +430,18 @@ impl GameContext {
     pub fn no_players_to_answer_left(&self) -> bool {
         let players_left = self
             .players
-            .iter()
-            .filter(|(_, p)| {
-                p.state != PlayerState::Inactive
-                    && p.state != PlayerState::Dead
-                    && p.state != PlayerState::AnsweredWrong
-            })
+            .values()
+            .filter(|&p| p.state != PlayerState::AnsweredWrong && self.player_allowed_to_click(p))
             .count();
         log::debug!("Players to answer left: {}", players_left);
         players_left == 0
     }
 
-    pub fn init_next_round(&mut self) {
+    pub fn init_next_round(&mut self) -> Result<(), GameplayError> {
+        self.ensure_command_allowed(GameCommand::StartNextRound)?;
         if self.is_already_last_round() {
-            log::error!("Already final round");
-            return;
+            return Err(Report::new(GameplayError::OperationForbidden))
+                .attach_printable("Already final round");
         }
 
         self.current.round_index += 1;
@@ -285,141 +453,89 @@ impl GameContext {
             .get(index)
             .expect(&format!("Expected to have round #{}", index));
         log::info!("Next round name {}", round.name);
+        self.journal.record(JournalEvent::RoundStarted { round_index: index });
 
         self.current.total_tries = 0;
         self.current.total_wrong_answers = 0;
         self.current.total_correct_answers = 0;
+        self.current.final_round = None;
 
         if self.is_already_last_round() {
-            self.kill_players_with_negative_balance();
+            self.kill_players_out_before_last_round();
+        }
+
+        if self.is_final_round() {
+            self.set_question_chooser(0);
+            self.start_final_round();
+            self.current.round_timer = RoundTimer::start(None);
+        } else {
+            self.assign_lowest_score_chooser();
+            self.current.round_timer = RoundTimer::start(self.round_duration);
         }
+        Ok(())
     }
 
     fn is_already_last_round(&mut self) -> bool {
         (self.game_pack.content.rounds.len() - 1) == self.current.round_index
     }
 
-    pub fn fetch_round_stats(&self) -> RoundStatsDto {
-        let round = self.get_current_round();
-        RoundStatsDto {
-            roundName: round.name.to_owned(),
-            questionNumber: round.question_count,
-            normalQuestionNum: round.normal_question_count,
-            pigInPokeQuestionNum: round.pip_question_count,
-            totalCorrectAnswers: self.current.total_correct_answers,
-            totalWrongAnswers: self.current.total_wrong_answers,
-            totalTries: self.current.total_tries,
-            roundTime: "Not tracked".to_owned(),
-            players: self
-                .players
-                .values()
-                .map(|p| PlayerStatsDto {
-                    id: p.term_id as i32,
-                    name: p.name.to_owned(),
-                    score: p.stats.score,
-                    playerIconPath: p.icon.to_owned(),
-                    totalAnswers: p.stats.total_tries,
-                    answeredCorrectly: p.stats.correct_num,
-                    answeredWrong: p.stats.wrong_num,
-                })
-                .collect(),
+    pub(crate) fn update_game_state(
+        &mut self,
+        new_state: GameState,
+        command: GameCommand,
+    ) -> Result<(), GameplayError> {
+        self.validate_transition(&new_state, command)?;
+        self.record_transition(self.current.game_state().clone(), new_state.clone(), command);
+        match new_state {
+            GameState::QuestionSelected => {
+                self.current.round_timer.set_paused_by_game(true);
+                self.allow_answer_timestamp.swap(u32::MAX, Ordering::Relaxed);
+                self.open_false_start_window();
+                self.highlight_question_chooser(false);
+            }
+            GameState::QuestionChoosing => {
+                self.current.round_timer.set_paused_by_game(false);
+                self.release_all_lockouts();
+                self.highlight_question_chooser(true);
+                self.settle_tie_break();
+            }
+            GameState::AnswerWrong => self.open_false_start_window(),
+            _ => {}
         }
-    }
-
-    fn update_game_state(&mut self, new_state: GameState) {
-        log::info!(
-            "Game state {:?} -> {:?}",
-            self.current.game_state(),
-            new_state
-        );
         self.current.set_game_state(new_state);
         self.update_non_target_player_states();
+        Ok(())
     }
 
-    fn get_question(
-        &mut self,
-        theme: &String,
-        price: &i32,
-    ) -> Result<(Question, i32), GamePackError> {
-        let round = self.get_current_round_mut();
+    fn get_question(&mut self, id: QuestionId) -> Result<(Question, i32), GamePackError> {
+        let round = self.get_current_round();
         let question_number = round.question_count - round.questions_left;
 
-        let theme = round
-            .themes
-            .get_mut(theme)
-            .ok_or(GamePackError::ThemeNotPresent)
-            .into_report()
-            .attach_printable(format!("Can't find theme: {theme:?}"))?;
-
+        let theme = self.get_pack_theme_mut(id)?;
+        let theme_name = theme.name.clone();
         let question = theme
-            .get_question(price)
+            .get_question(id.question)
             .ok_or(GamePackError::QuestionNotPresent)
             .into_report()
-            .attach_printable(format!(
-                "Can't find question with price {price:?} in theme: {theme:?}"
-            ))?
+            .attach_printable(format!("Can't find question {id} in theme: {theme_name:?}"))?
             .clone();
 
-        self.current.question_theme = theme.name.clone();
+        self.current.question_id = id;
+        self.current.question_theme = theme_name;
         self.current.question_type = question.question_type.clone();
-        self.current.question_price = question.price;
+        self.current.answer_scene = question.answer_scenario.clone();
+        self.current.host_notes = question.host_notes.clone();
+        self.current.question_price = question.play_price();
+        self.current.question_nominal_price = question.price;
+        self.current.question_tries = 0;
+        self.current.auction = None;
         Ok((question, question_number))
     }
 
-    fn get_fastest_click_from_hub(&mut self) -> Result<u8, HubManagerError> {
-        let Some(receiver) = &self.event_queue else {
-            return Err(HubManagerError::NotInitializedError.into());
-        };
-
-        let start_time = Instant::now();
-        let timeout = Duration::from_secs(10);
-        let fastest_click: Option<u8> = None;
-
-        loop {
-            if start_time.elapsed() >= timeout {
-                return Err(Report::new(HubManagerError::NoResponseFromTerminal));
-            }
-
-            let events = match Self::get_events(receiver) {
-                Ok(events) => events,
-                Err(_) => {
-                    sleep(Duration::from_millis(100));
-                    continue;
-                }
-            };
-
-            let base_timestamp = self.allow_answer_timestamp.load(Ordering::Relaxed);
-            let mut events: Vec<TermEvent> = events.iter()
-                .filter(|&e| {
-                    return if e.timestamp >= base_timestamp {
-                        log::info!("After answer allowed. Event {:?}", e);
-                        true
-                    } else {
-                        log::info!("Answer too early. Event {:?}", e);
-                        false
-                    }
-                })
-                .map(|e| e.clone())
-                .collect();
-
-            events.sort_by(|e1, e2| e1.timestamp.cmp(&e2.timestamp));
-
-            if let Some(value) = self.find_the_fastest_event(&mut events) {
-                return value;
-            }
-
-            if let Some(fastest_click_id) = fastest_click {
-                return Ok(fastest_click_id);
-            }
-
-            sleep(Duration::from_secs(1));
-        }
-    }
-
-    fn find_the_fastest_event(
+    pub(crate) fn find_the_fastest_event<'a>(
         &self,
-        events: &mut Vec<TermEvent>,
-    ) -> Option<Result<u8, HubManagerError>> {
+        events: &'a [TermEvent],
+    ) -> Option<&'a TermEvent> {
         for e in events {
             if e.state != Pressed {
                 log::debug!("Release event. Skipping: {:?}", e);
@@ -431,7 +547,7 @@ impl GameContext {
                 continue;
             };
 
-            if !player.allowed_to_click() {
+            if !self.player_allowed_to_click(player) {
                 log::debug!(
                     "Player {} is not allowed to click. Skipping: {:?}",
                     e.term_id,
@@ -441,38 +557,30 @@ impl GameContext {
             }
 
             log::info!("Found the fastest click: {:?}", e);
-            return Some(Ok(e.term_id));
+            return Some(e);
         }
         None
     }
 
-    fn get_events(receiver: &Receiver<TermEvent>) -> Result<Vec<TermEvent>, HubManagerError> {
-        let mut events: Vec<TermEvent> = Vec::new();
-        loop {
-            match receiver.try_recv() {
-                Ok(received_event) => {
-                    events.push(received_event);
-                }
-                Err(mpsc::TryRecvError::Empty) => {
-                    log::debug!("Got {} events for now.", events.len());
-                    break;
-                }
-                Err(mpsc::TryRecvError::Disconnected) => {
-                    // Channel has been disconnected, so we break the loop
-                    let report = Report::new(HubManagerError::InternalError)
-                        .attach_printable("Pipe disconnected: mpsc::TryRecvError::Disconnected");
-                    return Err(report);
-                }
-            }
-        }
-
-        Ok(events)
-    }
-
     fn get_player_keys(&self) -> Vec<u8> {
         self.players.keys().copied().collect()
     }
 
+    /// Theme of the question in the current round
+    fn get_pack_theme_mut(&mut self, id: QuestionId) -> Result<&mut Theme, GamePackError> {
+        if id.round != self.current.round_index {
+            return Err(Report::new(GamePackError::ThemeNotPresent))
+                .attach_printable(format!("Round of question {id} is not played now"));
+        }
+
+        self.get_current_round_mut()
+            .themes
+            .get_mut(id.theme)
+            .ok_or(GamePackError::ThemeNotPresent)
+            .into_report()
+            .attach_printable(format!("Can't find theme of question {id}"))
+    }
+
     fn get_current_round_mut(&mut self) -> &mut Round {
         let index = self.current.round_index;
         let round = self.game_pack.content.rounds.get_mut(index)
@@ -483,6 +591,13 @@ impl GameContext {
     fn update_non_target_player_states(&mut self) {
         let game_state = self.current.game_state();
         let active_id = self.get_active_player_id();
+        let chooser_id = self.current.question_chooser_id;
+        let sitting_out: Vec<u8> = self
+            .players
+            .keys()
+            .copied()
+            .filter(|&id| self.sits_out_tie_break(id))
+            .collect();
 
         self.players.iter_mut().for_each(|(id, p)| {
             log::debug!(
@@ -492,6 +607,18 @@ impl GameContext {
                 p.state
             );
 
+            if *game_state == GameState::QuestionChoosing && p.term_id == chooser_id {
+                log::trace!("Player with id {} chooses the question", id);
+                p.state = PlayerState::QuestionChooser;
+                return;
+            }
+
+            if sitting_out.contains(&p.term_id) {
+                log::trace!("Player with id {} sits the tie-break out", id);
+                p.state = PlayerState::Inactive;
+                return;
+            }
+
             if p.term_id == active_id {
                 log::debug!("Active player. Skipping");
                 return;
@@ -511,11 +638,12 @@ impl GameContext {
         });
     }
 
-    fn kill_players_with_negative_balance(&mut self) {
+    fn kill_players_out_before_last_round(&mut self) {
+        let rules = self.rules();
         self.players.iter_mut().for_each(|(_, player)| {
-            if player.stats.score < 0 {
+            if rules.out_before_last_round(player) {
                 log::info!(
-                    "Killing player {:?} because of the negative balance",
+                    "Killing player {:?} before the last round",
                     player
                 );
                 player.state = PlayerState::Dead;
@@ -553,9 +681,13 @@ fn listen_hub_events(
             continue;
         }
 
-        events.iter().for_each(|e| {
-            process_term_event(&hub_guard, e, &sender);
-        });
+        let is_sent = events
+            .iter()
+            .all(|e| process_term_event(&hub_guard, e, &sender));
+        if !is_sent {
+            log::info!("Events are not consumed anymore. Stopping event listener");
+            break;
+        }
     }
 }
 
@@ -563,7 +695,7 @@ fn process_term_event(
     hub_guard: &RwLockReadGuard<Box<dyn HubManager>>,
     e: &TermEvent,
     sender: &Sender<TermEvent>
-) {
+) -> bool {
     hub_guard
         .set_term_feedback_led(e.term_id, &e.state)
         .unwrap_or_else(|error| {
@@ -575,5 +707,5 @@ fn process_term_event(
         .map_err(|e| {
             log::error!("Can't send the event: {}", e);
         })
-        .unwrap_or_default();
+        .is_ok()
 }
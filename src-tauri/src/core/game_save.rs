@@ -0,0 +1,357 @@
+use error_stack::{IntoReport, Report, Result, ResultExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+
+use crate::api::dto::QuestionType;
+use crate::core::false_start::FalseStartPenalty;
+use crate::core::game_entities::{CurrentContext, GameContext, GameState, Player, PlayerState};
+use crate::core::game_state_machine::GameCommand;
+use crate::core::round_timer::RoundTimer;
+use crate::core::rule_set::RuleSetPreset;
+use crate::core::score_adjustment::ScoreAdjustment;
+use crate::core::team::Team;
+use crate::core::tie_break::{load_tie_break_round, TieBreak};
+use crate::game_pack::game_pack_entites::GamePack;
+use crate::game_pack::pack_content_entities::QuestionId;
+
+const SAVE_FILE_NAME: &str = "last_game.json";
+
+#[derive(Debug, Clone, Serialize, Error)]
+pub enum GameSaveError {
+    #[error("No saved game")]
+    NoSavedGame,
+    #[error("Saved game is corrupted")]
+    CorruptedSave,
+    #[error("Pack of the saved game can't be loaded")]
+    PackNotAvailable,
+    #[error("Pack was changed since the game was saved")]
+    PackChanged,
+    #[error("Internal error")]
+    InternalError,
+}
+
+/// Question that was being played when the game was saved
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SavedQuestion {
+    pub id: QuestionId,
+    pub price: i32,
+    pub question_type: QuestionType,
+}
+
+/// Game state persisted to disk so the game survives an app crash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameSave {
+    pub pack_path: String,
+    pub pack_hash: String,
+    pub players: HashMap<u8, Player>,
+    pub round_index: usize,
+    /// Indices of the questions left in each theme of the current round, in the theme order
+    pub remaining_questions: Vec<Vec<usize>>,
+    pub current_question: Option<SavedQuestion>,
+    pub active_player_id: u8,
+    #[serde(default)]
+    pub question_chooser_id: u8,
+    pub total_correct_answers: i32,
+    pub total_wrong_answers: i32,
+    pub total_tries: i32,
+    pub round_elapsed_sec: u64,
+    pub round_duration_sec: Option<u64>,
+    pub answer_window_sec: Option<u64>,
+    #[serde(default)]
+    pub false_start_penalty: FalseStartPenalty,
+    #[serde(default)]
+    pub rule_set: RuleSetPreset,
+    #[serde(default)]
+    pub teams: HashMap<u8, Team>,
+    /// Tie-break being played. Its round is rebuilt on top of the pack
+    #[serde(default)]
+    pub tie_break: Option<TieBreak>,
+    pub score_adjustments: Vec<ScoreAdjustment>,
+    /// Journal file of the game, continued once the game is resumed
+    #[serde(default)]
+    pub journal_path: Option<PathBuf>,
+}
+
+impl GameContext {
+    pub fn to_game_save(&self) -> GameSave {
+        let round = self.get_current_round();
+        let remaining_questions = round
+            .themes
+            .iter()
+            .map(|theme| {
+                theme
+                    .remaining_questions()
+                    .map(|(index, _)| index)
+                    .collect()
+            })
+            .collect();
+
+        let current_question = match self.current.game_state() {
+            GameState::SetupAndLoading | GameState::QuestionChoosing => None,
+            _ => Some(SavedQuestion {
+                id: self.current.question_id,
+                price: self.current.question_price,
+                question_type: self.current.question_type.clone(),
+            }),
+        };
+
+        GameSave {
+            pack_path: self.game_pack.archive_path.clone(),
+            pack_hash: self.game_pack.archive_hash.clone(),
+            players: self.players.clone(),
+            round_index: self.current.round_index,
+            remaining_questions,
+            current_question,
+            active_player_id: self.current.active_player_id(),
+            question_chooser_id: self.current.question_chooser_id,
+            total_correct_answers: self.current.total_correct_answers,
+            total_wrong_answers: self.current.total_wrong_answers,
+            total_tries: self.current.total_tries,
+            round_elapsed_sec: self.current.round_timer.elapsed().as_secs(),
+            round_duration_sec: self.round_duration.map(|d| d.as_secs()),
+            answer_window_sec: self.answer_window.map(|d| d.as_secs()),
+            false_start_penalty: self.false_start_penalty,
+            rule_set: self.rule_set,
+            teams: self.teams.clone(),
+            tie_break: self.current.tie_break.clone(),
+            score_adjustments: self.score_adjustments.clone(),
+            journal_path: self.journal.file_path().cloned(),
+        }
+    }
+
+    /// Writes the game snapshot to the data dir. Does nothing until the game is started.
+    /// Failures are only logged: the game must go on even if the snapshot can't be written
+    pub fn save_game(&self) {
+        let Some(data_dir) = &self.data_dir else {
+            return;
+        };
+        if *self.current.game_state() == GameState::SetupAndLoading {
+            return;
+        }
+
+        write_game_save(data_dir, &self.to_game_save()).unwrap_or_else(|e| {
+            log::error!("Can't save the game: {:?}", e);
+        });
+    }
+
+    /// Restores the saved game on top of the freshly loaded pack.
+    ///
+    /// The game is resumed on the round board: a question interrupted by the crash stays on
+    /// the board and has to be selected again. Final round is restarted from the beginning.
+    pub fn restore_game(&mut self, save: GameSave, pack: GamePack) -> Result<(), GameSaveError> {
+        if pack.archive_hash != save.pack_hash {
+            return Err(Report::new(GameSaveError::PackChanged)).attach_printable(format!(
+                "Pack {} hash is {}, saved game expects {}",
+                save.pack_path, pack.archive_hash, save.pack_hash
+            ));
+        }
+
+        let mut pack = pack;
+        if let Some(tie_break) = &save.tie_break {
+            let round = load_tie_break_round(&pack, &tie_break.source)
+                .change_context(GameSaveError::PackNotAvailable)?;
+            pack.content.rounds.push(round);
+        }
+
+        if save.round_index >= pack.content.rounds.len() {
+            return Err(Report::new(GameSaveError::CorruptedSave)).attach_printable(format!(
+                "Saved round #{} is not present in pack with {} rounds",
+                save.round_index,
+                pack.content.rounds.len()
+            ));
+        }
+
+        self.game_pack = pack;
+        self.players = save.players;
+        self.players.values_mut().for_each(|p| {
+            if p.state != PlayerState::Dead {
+                p.state = PlayerState::Idle;
+            }
+        });
+        self.round_duration = save.round_duration_sec.map(Duration::from_secs);
+        self.answer_window = save.answer_window_sec.map(Duration::from_secs);
+        self.false_start_penalty = save.false_start_penalty;
+        self.rule_set = save.rule_set;
+        self.teams = save.teams;
+        self.score_adjustments = save.score_adjustments;
+        self.history.clear();
+
+        let round = &mut self.game_pack.content.rounds[save.round_index];
+        for (theme_index, theme) in round.themes.iter_mut().enumerate() {
+            let remaining = save.remaining_questions.get(theme_index);
+            for (index, question) in theme.questions.iter_mut().enumerate() {
+                question.played = !remaining.is_some_and(|r| r.contains(&index));
+            }
+        }
+        round.questions_left = round
+            .themes
+            .iter()
+            .map(|theme| theme.remaining_questions().count() as i32)
+            .sum();
+
+        self.current = CurrentContext::default();
+        self.current.round_index = save.round_index;
+        self.current.total_correct_answers = save.total_correct_answers;
+        self.current.total_wrong_answers = save.total_wrong_answers;
+        self.current.total_tries = save.total_tries;
+        self.current.set_active_player_id(save.active_player_id);
+        self.current.question_chooser_id = match save.question_chooser_id {
+            0 => save.active_player_id,
+            chooser_id => chooser_id,
+        };
+        self.current.tie_break = save.tie_break;
+        self.deactivate_tie_break_spectators();
+        self.state_transitions.clear();
+        self.record_transition(
+            GameState::SetupAndLoading,
+            GameState::QuestionChoosing,
+            GameCommand::ResumeGame,
+        );
+        self.current.set_game_state(GameState::QuestionChoosing);
+
+        if let Some(question) = &save.current_question {
+            log::info!(
+                "Question {} was interrupted and returns to the board",
+                question.id
+            );
+        }
+
+        if self.is_final_round() {
+            self.start_final_round();
+            self.current.round_timer = RoundTimer::start(None);
+        } else {
+            let elapsed = Duration::from_secs(save.round_elapsed_sec);
+            let duration = match self.current.tie_break {
+                Some(_) => None,
+                None => self.round_duration,
+            };
+            self.current.round_timer = RoundTimer::resume(duration, elapsed);
+        }
+
+        log::info!(
+            "Game restored: round #{}, {} questions left",
+            save.round_index,
+            self.get_current_round().questions_left
+        );
+        Ok(())
+    }
+
+    /// Restores the game from its own state, the way it was saved before the crash.
+    /// Used to replay the resume recorded in the journal
+    pub(crate) fn resume_saved_state(&mut self) -> Result<(), GameSaveError> {
+        let save = self.to_game_save();
+        let mut pack = self.game_pack.clone();
+        // Tie-break round is rebuilt on top of the pack
+        if save.tie_break.is_some() {
+            pack.content.rounds.pop();
+        }
+        self.restore_game(save, pack)
+    }
+}
+
+pub fn read_game_save(data_dir: &Path) -> Result<GameSave, GameSaveError> {
+    let path = data_dir.join(SAVE_FILE_NAME);
+    if !path.exists() {
+        return Err(Report::new(GameSaveError::NoSavedGame))
+            .attach_printable(format!("Save file {:?} not found", path));
+    }
+
+    let content = fs::read_to_string(&path)
+        .into_report()
+        .change_context(GameSaveError::InternalError)
+        .attach_printable(format!("Can't read save file {:?}", path))?;
+
+    serde_json::from_str(&content)
+        .into_report()
+        .change_context(GameSaveError::CorruptedSave)
+        .attach_printable(format!("Can't parse save file {:?}", path))
+}
+
+fn write_game_save(data_dir: &Path, save: &GameSave) -> Result<(), GameSaveError> {
+    let path = save_file_path(data_dir)?;
+    let content = serde_json::to_string_pretty(save)
+        .into_report()
+        .change_context(GameSaveError::InternalError)
+        .attach_printable("Can't serialize the game")?;
+
+    // Write to the temporary file first so a crash in the middle doesn't spoil the last save
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, content)
+        .into_report()
+        .change_context(GameSaveError::InternalError)
+        .attach_printable(format!("Can't write save file {:?}", tmp_path))?;
+
+    fs::rename(&tmp_path, &path)
+        .into_report()
+        .change_context(GameSaveError::InternalError)
+        .attach_printable(format!("Can't replace save file {:?}", path))
+}
+
+fn save_file_path(data_dir: &Path) -> Result<PathBuf, GameSaveError> {
+    fs::create_dir_all(data_dir)
+        .into_report()
+        .change_context(GameSaveError::InternalError)
+        .attach_printable(format!("Can't create data dir: {:?}", data_dir))?;
+
+    Ok(data_dir.join(SAVE_FILE_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_pack::pack_content_entities::test_pack::{make_pack, make_round, make_theme};
+
+    fn music_pack() -> GamePack {
+        make_pack(vec![make_round(vec![make_theme(
+            "Music",
+            &[100, 200, 300],
+        )])])
+    }
+
+    #[test]
+    fn test_game_is_restored_from_save() {
+        let mut ctx = GameContext::default();
+        ctx.game_pack = music_pack();
+        ctx.players.insert(1, Player::default());
+        ctx.players.get_mut(&1).expect("Test").stats.score = 500;
+        ctx.game_pack.content.rounds[0].themes[0]
+            .pop_question(1)
+            .expect("Test");
+        ctx.current.set_game_state(GameState::QuestionChoosing);
+
+        let save = ctx.to_game_save();
+        let json = serde_json::to_string(&save).expect("Test");
+        let save: GameSave = serde_json::from_str(&json).expect("Test");
+
+        let mut restored = GameContext::default();
+        restored.restore_game(save, music_pack()).expect("Test");
+
+        let round = restored.get_current_round();
+        assert_eq!(round.questions_left, 2);
+        assert!(round.themes[0].get_question(1).is_none());
+        assert!(round.themes[0].get_question(2).is_some());
+        assert_eq!(restored.players[&1].stats.score, 500);
+        assert_eq!(*restored.current.game_state(), GameState::QuestionChoosing);
+    }
+
+    #[test]
+    fn test_changed_pack_is_rejected() {
+        let mut ctx = GameContext::default();
+        ctx.game_pack = music_pack();
+        let save = ctx.to_game_save();
+
+        let mut pack = music_pack();
+        pack.archive_hash = "7".to_string();
+        let error = GameContext::default()
+            .restore_game(save, pack)
+            .expect_err("Test");
+        assert!(matches!(
+            error.current_context(),
+            GameSaveError::PackChanged
+        ));
+    }
+}
@@ -0,0 +1,262 @@
+use error_stack::{Report, Result, ResultExt};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::api::dto::QuestionType;
+use crate::core::game_entities::{GameContext, GameState, GameplayError};
+
+/// Gameplay commands validated against the game state
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum GameCommand {
+    StartGame,
+    ResumeGame,
+    SelectQuestion,
+    PassPigInPoke,
+    PlaceAuctionBid,
+    AllowAnswer,
+    GetFastestClick,
+    JudgeAnswer,
+    FinishQuestion,
+    StartNextRound,
+    PlayFinalRound,
+    AdjustScore,
+    Undo,
+    Redo,
+    StartTieBreak,
+}
+
+impl GameCommand {
+    pub const ALL: [GameCommand; 15] = [
+        GameCommand::StartGame,
+        GameCommand::ResumeGame,
+        GameCommand::SelectQuestion,
+        GameCommand::PassPigInPoke,
+        GameCommand::PlaceAuctionBid,
+        GameCommand::AllowAnswer,
+        GameCommand::GetFastestClick,
+        GameCommand::JudgeAnswer,
+        GameCommand::FinishQuestion,
+        GameCommand::StartNextRound,
+        GameCommand::PlayFinalRound,
+        GameCommand::AdjustScore,
+        GameCommand::Undo,
+        GameCommand::Redo,
+        GameCommand::StartTieBreak,
+    ];
+
+    pub fn is_allowed_in(&self, state: &GameState) -> bool {
+        use GameState::*;
+        match self {
+            GameCommand::StartGame | GameCommand::ResumeGame => true,
+            GameCommand::SelectQuestion
+            | GameCommand::StartNextRound
+            | GameCommand::PlayFinalRound
+            | GameCommand::StartTieBreak => *state == QuestionChoosing,
+            GameCommand::PassPigInPoke | GameCommand::PlaceAuctionBid => *state == QuestionSelected,
+            GameCommand::AllowAnswer => matches!(state, QuestionSelected | AnswerWrong),
+            GameCommand::GetFastestClick => *state == AnswerAllowed,
+            GameCommand::JudgeAnswer => *state == AnswerRequested,
+            GameCommand::FinishQuestion => matches!(
+                state,
+                QuestionSelected | AnswerAllowed | AnswerRequested | AnswerWrong
+            ),
+            GameCommand::AdjustScore | GameCommand::Undo | GameCommand::Redo => {
+                *state != SetupAndLoading
+            }
+        }
+    }
+
+    /// Commands which put the game into the state regardless of the transition table
+    pub fn resets_state(&self) -> bool {
+        matches!(
+            self,
+            GameCommand::StartGame
+                | GameCommand::ResumeGame
+                | GameCommand::Undo
+                | GameCommand::Redo
+        )
+    }
+}
+
+/// Allowed game state transitions
+const TRANSITIONS: [(GameState, GameState); 15] = [
+    (GameState::SetupAndLoading, GameState::QuestionChoosing),
+    (GameState::QuestionChoosing, GameState::QuestionSelected),
+    (GameState::QuestionSelected, GameState::AnswerAllowed),
+    // Pig in poke victim or auction winner answers without a click
+    (GameState::QuestionSelected, GameState::AnswerRequested),
+    (GameState::QuestionSelected, GameState::QuestionChoosing),
+    (GameState::AnswerAllowed, GameState::AnswerRequested),
+    (GameState::AnswerAllowed, GameState::QuestionChoosing),
+    (GameState::AnswerRequested, GameState::AnswerCorrect),
+    (GameState::AnswerRequested, GameState::AnswerWrong),
+    (GameState::AnswerRequested, GameState::QuestionChoosing),
+    (GameState::AnswerWrong, GameState::AnswerAllowed),
+    (GameState::AnswerWrong, GameState::NoPlayersToAnswerLeft),
+    (GameState::AnswerWrong, GameState::QuestionChoosing),
+    (GameState::AnswerCorrect, GameState::QuestionChoosing),
+    (
+        GameState::NoPlayersToAnswerLeft,
+        GameState::QuestionChoosing,
+    ),
+];
+
+pub fn is_transition_allowed(from: &GameState, to: &GameState) -> bool {
+    TRANSITIONS
+        .iter()
+        .any(|(allowed_from, allowed_to)| allowed_from == from && allowed_to == to)
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct StateTransition {
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    pub from: GameState,
+    pub to: GameState,
+    pub command: GameCommand,
+}
+
+impl GameContext {
+    pub fn ensure_command_allowed(&self, command: GameCommand) -> Result<(), GameplayError> {
+        let state = self.current.game_state();
+        if !command.is_allowed_in(state) {
+            return Err(Report::new(GameplayError::CommandNotAllowed {
+                command,
+                state: state.clone(),
+            }));
+        }
+
+        if !self.fits_question_type(command) {
+            return Err(Report::new(GameplayError::OperationForbidden)).attach_printable(format!(
+                "{:?} question is answered only by the assigned player",
+                self.current.question_type
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn allowed_commands(&self) -> Vec<GameCommand> {
+        GameCommand::ALL
+            .into_iter()
+            .filter(|c| c.is_allowed_in(self.current.game_state()) && self.fits_question_type(*c))
+            .collect()
+    }
+
+    /// Special questions go to the chooser, the pig in poke victim or the auction winner,
+    /// so nobody else may click for them
+    fn fits_question_type(&self, command: GameCommand) -> bool {
+        command != GameCommand::AllowAnswer || self.current.question_type == QuestionType::Normal
+    }
+
+    pub fn get_state_transitions(&self) -> &Vec<StateTransition> {
+        &self.state_transitions
+    }
+
+    pub(crate) fn validate_transition(
+        &self,
+        new_state: &GameState,
+        command: GameCommand,
+    ) -> Result<(), GameplayError> {
+        let state = self.current.game_state();
+        if command.resets_state() || is_transition_allowed(state, new_state) {
+            return Ok(());
+        }
+
+        Err(Report::new(GameplayError::TransitionNotAllowed {
+            from: state.clone(),
+            to: new_state.clone(),
+        }))
+    }
+
+    pub(crate) fn record_transition(
+        &mut self,
+        from: GameState,
+        to: GameState,
+        command: GameCommand,
+    ) {
+        log::info!("Game state {:?} -> {:?} by {:?}", from, to, command);
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+
+        self.state_transitions.push(StateTransition {
+            timestamp_ms,
+            from,
+            to,
+            command,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commands_out_of_order_are_rejected() {
+        let mut ctx = GameContext::default();
+        assert!(ctx
+            .ensure_command_allowed(GameCommand::AllowAnswer)
+            .is_err());
+
+        ctx.current.set_game_state(GameState::QuestionChoosing);
+        let error = ctx
+            .ensure_command_allowed(GameCommand::AllowAnswer)
+            .expect_err("Test");
+        assert!(matches!(
+            error.current_context(),
+            GameplayError::CommandNotAllowed {
+                command: GameCommand::AllowAnswer,
+                state: GameState::QuestionChoosing,
+            }
+        ));
+        assert!(ctx
+            .allowed_commands()
+            .contains(&GameCommand::SelectQuestion));
+        assert!(ctx
+            .validate_transition(&GameState::AnswerAllowed, GameCommand::AllowAnswer)
+            .is_err());
+    }
+
+    fn assert_answer_not_allowed_for(question_type: QuestionType) {
+        let mut ctx = GameContext::default();
+        ctx.current.question_type = question_type;
+        for state in [GameState::QuestionSelected, GameState::AnswerWrong] {
+            ctx.current.set_game_state(state);
+            let error = ctx
+                .ensure_command_allowed(GameCommand::AllowAnswer)
+                .expect_err("Test");
+            assert!(matches!(
+                error.current_context(),
+                GameplayError::OperationForbidden
+            ));
+            assert!(!ctx.allowed_commands().contains(&GameCommand::AllowAnswer));
+        }
+    }
+
+    #[test]
+    fn test_pig_in_poke_is_not_open_for_clicks() {
+        assert_answer_not_allowed_for(QuestionType::PigInPoke);
+    }
+
+    #[test]
+    fn test_auction_is_not_open_for_clicks() {
+        assert_answer_not_allowed_for(QuestionType::Auction);
+    }
+
+    #[test]
+    fn test_sponsored_question_is_not_open_for_clicks() {
+        assert_answer_not_allowed_for(QuestionType::Sponsored);
+    }
+
+    #[test]
+    fn test_normal_question_is_open_for_clicks() {
+        let mut ctx = GameContext::default();
+        ctx.current.set_game_state(GameState::QuestionSelected);
+        ctx.ensure_command_allowed(GameCommand::AllowAnswer)
+            .expect("Test");
+        assert!(ctx.allowed_commands().contains(&GameCommand::AllowAnswer));
+    }
+}
@@ -0,0 +1,95 @@
+use rgb::RGB8;
+
+use crate::core::answer_timer::LIGHT_OFF;
+use crate::core::game_entities::{GameContext, GameState, PlayerState};
+
+const LIGHT_QUESTION_CHOOSER: RGB8 = RGB8::new(0, 0, 255);
+
+impl GameContext {
+    /// Player whose turn is to select the next question. Zero if nobody chooses
+    pub fn get_question_chooser_id(&self) -> u8 {
+        self.current.question_chooser_id
+    }
+
+    /// Passes the turn to select questions. The chooser's terminal is lit while the question
+    /// is being chosen
+    pub(crate) fn set_question_chooser(&mut self, player_id: u8) {
+        let previous_id = self.current.question_chooser_id;
+        log::info!("Player {} chooses the next question", player_id);
+        self.current.question_chooser_id = player_id;
+
+        // Otherwise the turn passes once the game returns to choosing the question
+        if *self.current.game_state() == GameState::QuestionChoosing {
+            self.players.values_mut().for_each(|p| {
+                if p.term_id == player_id {
+                    p.state = PlayerState::QuestionChooser;
+                } else if p.state == PlayerState::QuestionChooser {
+                    p.state = PlayerState::Idle;
+                }
+            });
+            if previous_id != 0 && previous_id != player_id {
+                self.set_term_light(previous_id, LIGHT_OFF);
+            }
+            self.highlight_question_chooser(true);
+        }
+    }
+
+    /// Gives the turn to the player with the lowest score at the start of the round.
+    /// Ties go to the player with the lowest id
+    pub(crate) fn assign_lowest_score_chooser(&mut self) {
+        match self.lowest_score_player_id() {
+            Some(player_id) => self.set_question_chooser(player_id),
+            None => log::error!("Nobody is left to choose questions"),
+        }
+    }
+
+    /// Player with the lowest score among those still in the game. Ties go to the lowest id
+    pub(crate) fn lowest_score_player_id(&self) -> Option<u8> {
+        self.players
+            .values()
+            .filter(|p| p.state != PlayerState::Dead)
+            .min_by_key(|p| (p.stats.score, p.term_id))
+            .map(|p| p.term_id)
+    }
+
+    pub(crate) fn highlight_question_chooser(&self, highlighted: bool) {
+        let chooser_id = self.current.question_chooser_id;
+        if chooser_id == 0 {
+            return;
+        }
+
+        let color = if highlighted {
+            LIGHT_QUESTION_CHOOSER
+        } else {
+            LIGHT_OFF
+        };
+        self.set_term_light(chooser_id, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::game_entities::Player;
+    use crate::game_pack::pack_content_entities::Round;
+
+    #[test]
+    fn test_lowest_score_chooses_at_round_start() {
+        let mut ctx = GameContext::default();
+        for _ in 0..3 {
+            ctx.game_pack.content.rounds.push(Round::default());
+        }
+        for (id, score) in [(1, 300), (2, -100), (3, -100)] {
+            let mut player = Player::new(id);
+            player.stats.score = score;
+            ctx.players.insert(id, player);
+        }
+        ctx.begin_game().expect("Test");
+        ctx.assign_first_chooser(1).expect("Test");
+
+        ctx.init_next_round().expect("Test");
+        assert_eq!(ctx.get_question_chooser_id(), 2);
+        assert_eq!(ctx.players[&2].state, PlayerState::QuestionChooser);
+        assert_eq!(ctx.players[&1].state, PlayerState::Idle);
+    }
+}
@@ -0,0 +1,140 @@
+use std::time::{Duration, Instant};
+
+/// Round clock.
+///
+/// The clock runs only while neither the host nor the game holds it on pause. The game pauses
+/// it while a question is being played, so only the time spent on the round board is counted.
+#[derive(Debug, Default, Clone)]
+pub struct RoundTimer {
+    duration: Option<Duration>,
+    elapsed_before_pause: Duration,
+    running_since: Option<Instant>,
+    paused_by_host: bool,
+    paused_by_game: bool,
+}
+
+impl RoundTimer {
+    /// Creates a started timer. Without duration the round is not limited in time
+    pub fn start(duration: Option<Duration>) -> Self {
+        Self {
+            duration,
+            running_since: Some(Instant::now()),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a started timer which already counted `elapsed` time. Used to resume saved game
+    pub fn resume(duration: Option<Duration>, elapsed: Duration) -> Self {
+        Self {
+            elapsed_before_pause: elapsed,
+            ..Self::start(duration)
+        }
+    }
+
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        let running = self
+            .running_since
+            .map(|since| since.elapsed())
+            .unwrap_or_default();
+        self.elapsed_before_pause + running
+    }
+
+    pub fn remaining(&self) -> Option<Duration> {
+        self.duration
+            .map(|duration| duration.saturating_sub(self.elapsed()))
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining()
+            .map(|remaining| remaining.is_zero())
+            .unwrap_or(false)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.running_since.is_none()
+    }
+
+    pub fn is_paused_by_host(&self) -> bool {
+        self.paused_by_host
+    }
+
+    pub fn set_paused_by_host(&mut self, paused: bool) {
+        self.paused_by_host = paused;
+        self.update_running_state();
+    }
+
+    pub fn set_paused_by_game(&mut self, paused: bool) {
+        self.paused_by_game = paused;
+        self.update_running_state();
+    }
+
+    fn update_running_state(&mut self) {
+        let should_run = !self.paused_by_host && !self.paused_by_game;
+        match (should_run, self.running_since) {
+            (true, None) => {
+                self.running_since = Some(Instant::now());
+            }
+            (false, Some(since)) => {
+                self.elapsed_before_pause += since.elapsed();
+                self.running_since = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::game_entities::{GameContext, Player};
+    use crate::game_pack::pack_content_entities::test_pack::{make_pack, make_round, make_theme};
+    use crate::game_pack::pack_content_entities::QuestionId;
+    use std::thread::sleep;
+
+    #[test]
+    fn test_timer_does_not_run_on_pause() {
+        let mut timer = RoundTimer::start(Some(Duration::from_millis(100)));
+        timer.set_paused_by_game(true);
+        let elapsed = timer.elapsed();
+
+        sleep(Duration::from_millis(120));
+        assert_eq!(timer.elapsed(), elapsed);
+        assert!(!timer.is_expired());
+
+        timer.set_paused_by_host(true);
+        timer.set_paused_by_game(false);
+        assert!(timer.is_paused());
+
+        timer.set_paused_by_host(false);
+        sleep(Duration::from_millis(120));
+        assert!(timer.is_expired());
+        assert_eq!(timer.remaining(), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_unlimited_timer_never_expires() {
+        let timer = RoundTimer::start(None);
+
+        assert!(!timer.is_expired());
+        assert_eq!(timer.remaining(), None);
+    }
+
+    #[test]
+    fn test_question_is_not_selected_after_round_time() {
+        let mut ctx = GameContext::default();
+        ctx.game_pack = make_pack(vec![make_round(vec![make_theme("Music", &[100])])]);
+        ctx.players.insert(1, Player::new(1));
+        ctx.begin_game().expect("Test");
+        ctx.assign_first_chooser(1).expect("Test");
+        ctx.current.round_timer =
+            RoundTimer::resume(Some(Duration::from_secs(60)), Duration::from_secs(61));
+
+        assert!(ctx.get_pack_question(QuestionId::default()).is_err());
+        assert!(!ctx.has_next_question());
+        assert_eq!(ctx.get_current_round().questions_left, 1);
+    }
+}
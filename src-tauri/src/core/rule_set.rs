@@ -0,0 +1,169 @@
+use error_stack::{Report, Result, ResultExt};
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+
+use crate::core::game_entities::{GameContext, GameState, GameplayError, Player};
+
+/// House rules the game is played by: how answers are scored and who may answer
+pub trait RuleSet: Debug + Send + Sync {
+    /// Score change for the correct answer on the question of the given price
+    fn correct_answer_score(&self, price: i32) -> i32 {
+        price
+    }
+
+    /// Score change for the wrong answer on the question of the given price
+    fn wrong_answer_score(&self, price: i32) -> i32 {
+        -price
+    }
+
+    /// Whether the player may click for the question after the given number of answers on it
+    fn allowed_to_click(&self, player: &Player, _question_tries: usize) -> bool {
+        player.allowed_to_click()
+    }
+
+    /// Whether the player is out of the game once the last round starts
+    fn out_before_last_round(&self, player: &Player) -> bool {
+        player.stats.score < 0
+    }
+
+    /// Whether the player takes part in the final round
+    fn plays_final(&self, player: &Player) -> bool {
+        player.stats.score > 0
+    }
+}
+
+/// Built-in rule sets the host chooses from before the game starts
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum RuleSetPreset {
+    /// Wrong answer costs the question price. Negative scores are out once the last round starts
+    #[default]
+    Classic,
+    /// Wrong answers cost nothing
+    NoPenalty,
+    /// Wrong answer costs half of the question price
+    HalfPenalty,
+    /// Only one player answers each question: the question is over after the first wrong answer
+    OneBuzzPerQuestion,
+    /// Negative scores play all regular rounds and only miss the final
+    NoFinalForNegativeScores,
+}
+
+impl RuleSetPreset {
+    pub fn rules(&self) -> &'static dyn RuleSet {
+        match self {
+            RuleSetPreset::Classic => &ClassicRules,
+            RuleSetPreset::NoPenalty => &NoPenaltyRules,
+            RuleSetPreset::HalfPenalty => &HalfPenaltyRules,
+            RuleSetPreset::OneBuzzPerQuestion => &OneBuzzPerQuestionRules,
+            RuleSetPreset::NoFinalForNegativeScores => &NoFinalForNegativeScoresRules,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ClassicRules;
+
+impl RuleSet for ClassicRules {}
+
+#[derive(Debug)]
+struct NoPenaltyRules;
+
+impl RuleSet for NoPenaltyRules {
+    fn wrong_answer_score(&self, _price: i32) -> i32 {
+        0
+    }
+}
+
+#[derive(Debug)]
+struct HalfPenaltyRules;
+
+impl RuleSet for HalfPenaltyRules {
+    fn wrong_answer_score(&self, price: i32) -> i32 {
+        -price / 2
+    }
+}
+
+#[derive(Debug)]
+struct OneBuzzPerQuestionRules;
+
+impl RuleSet for OneBuzzPerQuestionRules {
+    fn allowed_to_click(&self, player: &Player, question_tries: usize) -> bool {
+        question_tries == 0 && player.allowed_to_click()
+    }
+}
+
+#[derive(Debug)]
+struct NoFinalForNegativeScoresRules;
+
+impl RuleSet for NoFinalForNegativeScoresRules {
+    fn out_before_last_round(&self, _player: &Player) -> bool {
+        false
+    }
+}
+
+impl GameContext {
+    /// Rules can't be changed once the game is started: the played questions were scored
+    /// by the previous ones
+    pub fn set_rule_set(&mut self, preset: RuleSetPreset) -> Result<(), GameplayError> {
+        if *self.current.game_state() != GameState::SetupAndLoading {
+            return Err(Report::new(GameplayError::OperationForbidden))
+                .attach_printable("Rule set can't be changed during the game");
+        }
+
+        log::info!("Rule set: {:?}", preset);
+        self.rule_set = preset;
+        Ok(())
+    }
+
+    pub(crate) fn rules(&self) -> &'static dyn RuleSet {
+        self.rule_set.rules()
+    }
+
+    /// Whether the player may click for the question being played
+    pub(crate) fn player_allowed_to_click(&self, player: &Player) -> bool {
+        self.rules()
+            .allowed_to_click(player, self.current.question_tries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::game_entities::PlayerState;
+
+    #[test]
+    fn test_one_buzz_per_question() {
+        let mut ctx = GameContext::default();
+        ctx.players.insert(1, Player::new(1));
+        ctx.players.insert(2, Player::new(2));
+        ctx.set_rule_set(RuleSetPreset::OneBuzzPerQuestion)
+            .expect("Test");
+        assert!(!ctx.no_players_to_answer_left());
+
+        // Players left out of the question didn't answer it
+        ctx.players.get_mut(&1).expect("Test").state = PlayerState::Inactive;
+        assert!(ctx.player_allowed_to_click(&ctx.players[&2]));
+
+        ctx.players.get_mut(&1).expect("Test").state = PlayerState::AnsweredWrong;
+        ctx.current.question_tries = 1;
+        assert!(!ctx.player_allowed_to_click(&ctx.players[&2]));
+        assert!(ctx.no_players_to_answer_left());
+
+        ctx.set_rule_set(RuleSetPreset::Classic).expect("Test");
+        assert!(!ctx.no_players_to_answer_left());
+        assert_eq!(ctx.rules().wrong_answer_score(300), -300);
+        assert_eq!(
+            RuleSetPreset::HalfPenalty.rules().wrong_answer_score(300),
+            -150
+        );
+    }
+
+    #[test]
+    fn test_rule_set_is_fixed_once_game_started() {
+        let mut ctx = GameContext::default();
+        ctx.current.set_game_state(GameState::QuestionChoosing);
+
+        assert!(ctx.set_rule_set(RuleSetPreset::NoPenalty).is_err());
+        assert_eq!(ctx.rule_set, RuleSetPreset::Classic);
+    }
+}
@@ -0,0 +1,247 @@
+use error_stack::{Report, Result, ResultExt};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::core::game_entities::{GameContext, GameplayError};
+use crate::core::game_journal::JournalEvent;
+use crate::core::game_state_machine::GameCommand;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ScoreAdjustmentType {
+    Add,
+    Subtract,
+    Set,
+}
+
+/// Host correction of a player's score, kept in the game audit log
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ScoreAdjustment {
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    pub round_index: usize,
+    pub round_name: String,
+    /// Theme and nominal price of the question played last when the adjustment was made
+    pub question: Option<(String, i32)>,
+    pub player_id: u8,
+    pub adjustment_type: ScoreAdjustmentType,
+    pub amount: i32,
+    pub score_before: i32,
+    pub score_after: i32,
+    pub reason: String,
+}
+
+impl ScoreAdjustment {
+    /// Entry setting the score the adjustment started from
+    fn undone(&self) -> ScoreAdjustment {
+        ScoreAdjustment {
+            timestamp_ms: now_ms(),
+            adjustment_type: ScoreAdjustmentType::Set,
+            amount: self.score_before,
+            score_before: self.score_after,
+            score_after: self.score_before,
+            reason: format!("Undone: {}", self.reason),
+            ..self.clone()
+        }
+    }
+
+    /// Entry setting the score the adjustment ended with
+    fn redone(&self) -> ScoreAdjustment {
+        ScoreAdjustment {
+            timestamp_ms: now_ms(),
+            adjustment_type: ScoreAdjustmentType::Set,
+            amount: self.score_after,
+            reason: format!("Redone: {}", self.reason),
+            ..self.clone()
+        }
+    }
+}
+
+impl ScoreAdjustmentType {
+    fn apply(&self, score: i32, amount: i32) -> i32 {
+        match self {
+            ScoreAdjustmentType::Add => score + amount,
+            ScoreAdjustmentType::Subtract => score - amount,
+            ScoreAdjustmentType::Set => amount,
+        }
+    }
+}
+
+impl GameContext {
+    /// Changes the player's score bypassing the answer judgment. The adjustment is written to
+    /// the audit log and can be undone like any other host action
+    pub fn adjust_player_score(
+        &mut self,
+        player_id: u8,
+        adjustment_type: ScoreAdjustmentType,
+        amount: i32,
+        reason: String,
+    ) -> Result<i32, GameplayError> {
+        self.ensure_command_allowed(GameCommand::AdjustScore)?;
+        if reason.trim().is_empty() {
+            return Err(Report::new(GameplayError::OperationForbidden))
+                .attach_printable("Score adjustment requires a reason");
+        }
+
+        if adjustment_type != ScoreAdjustmentType::Set && amount < 0 {
+            return Err(Report::new(GameplayError::OperationForbidden)).attach_printable(format!(
+                "Amount to {:?} can't be negative: {}",
+                adjustment_type, amount
+            ));
+        }
+
+        let description = format!(
+            "Score of player {}: {:?} {} ({})",
+            player_id, adjustment_type, amount, reason
+        );
+        self.record_action(description, |ctx| {
+            ctx.apply_score_adjustment(player_id, adjustment_type, amount, reason)
+        })
+    }
+
+    pub fn get_score_adjustments(&self) -> &Vec<ScoreAdjustment> {
+        &self.score_adjustments
+    }
+
+    pub(crate) fn log_undone_score_adjustments(&mut self, adjustments: &[ScoreAdjustment]) {
+        let undone = adjustments.iter().rev().map(ScoreAdjustment::undone);
+        self.score_adjustments.extend(undone);
+    }
+
+    pub(crate) fn log_redone_score_adjustments(&mut self, adjustments: &[ScoreAdjustment]) {
+        let redone = adjustments.iter().map(ScoreAdjustment::redone);
+        self.score_adjustments.extend(redone);
+    }
+
+    fn apply_score_adjustment(
+        &mut self,
+        player_id: u8,
+        adjustment_type: ScoreAdjustmentType,
+        amount: i32,
+        reason: String,
+    ) -> Result<i32, GameplayError> {
+        let round_index = self.current.round_index;
+        let round_name = self
+            .game_pack
+            .content
+            .rounds
+            .get(round_index)
+            .map(|r| r.name.clone())
+            .unwrap_or_default();
+        let question = if self.current.question_theme.is_empty() {
+            None
+        } else {
+            Some((
+                self.current.question_theme.clone(),
+                self.current.question_nominal_price,
+            ))
+        };
+
+        let player = self
+            .players
+            .get_mut(&player_id)
+            .ok_or(Report::new(GameplayError::PlayerNotPresent))
+            .attach_printable(format!("Can't find player with id {}", player_id))?;
+
+        let score_before = player.stats.score;
+        player.stats.score = adjustment_type.apply(score_before, amount);
+        let score_after = player.stats.score;
+
+        log::info!(
+            "Score of player {} adjusted: {} -> {}. Reason: {}",
+            player_id,
+            score_before,
+            score_after,
+            reason
+        );
+
+        self.journal.record(JournalEvent::ScoreAdjusted {
+            player_id,
+            adjustment_type,
+            amount,
+            reason: reason.clone(),
+        });
+
+        self.score_adjustments.push(ScoreAdjustment {
+            timestamp_ms: now_ms(),
+            round_index,
+            round_name,
+            question,
+            player_id,
+            adjustment_type,
+            amount,
+            score_before,
+            score_after,
+            reason,
+        });
+        Ok(score_after)
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::game_entities::{GameState, Player};
+    use crate::game_pack::pack_content_entities::Round;
+
+    #[test]
+    fn test_score_adjustments_are_logged_and_undoable() {
+        let mut ctx = GameContext::default();
+        ctx.game_pack.content.rounds.push(Round::default());
+        ctx.players.insert(1, Player::default());
+        ctx.current.set_game_state(GameState::QuestionChoosing);
+
+        let reason = "Appeal accepted".to_string();
+        ctx.adjust_player_score(1, ScoreAdjustmentType::Add, 300, reason.clone())
+            .expect("Test");
+        ctx.adjust_player_score(1, ScoreAdjustmentType::Set, -100, reason.clone())
+            .expect("Test");
+        assert!(ctx
+            .adjust_player_score(1, ScoreAdjustmentType::Subtract, 100, " ".to_string())
+            .is_err());
+
+        assert_eq!(ctx.players[&1].stats.score, -100);
+        assert_eq!(ctx.get_score_adjustments().len(), 2);
+        assert_eq!(ctx.get_score_adjustments()[1].score_before, 300);
+
+        ctx.undo_last_action().expect("Test");
+        assert_eq!(ctx.players[&1].stats.score, 300);
+        assert_eq!(ctx.get_score_adjustments().len(), 3);
+        assert_eq!(ctx.get_score_adjustments()[2].score_after, 300);
+    }
+
+    #[test]
+    fn test_undo_keeps_corrections_in_audit_log() {
+        let mut ctx = GameContext::default();
+        ctx.game_pack.content.rounds.push(Round::default());
+        ctx.players.insert(1, Player::default());
+        ctx.current.set_game_state(GameState::QuestionChoosing);
+
+        let reason = "Appeal accepted".to_string();
+        ctx.adjust_player_score(1, ScoreAdjustmentType::Add, 300, reason.clone())
+            .expect("Test");
+        ctx.record_action("judgment".to_string(), |_| Ok(()))
+            .expect("Test");
+        ctx.undo_last_action().expect("Test");
+        ctx.undo_last_action().expect("Test");
+        assert_eq!(ctx.players[&1].stats.score, 0);
+
+        let log = ctx.get_score_adjustments();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].reason, reason);
+        assert_eq!(log[0].score_after, 300);
+        assert_eq!(log[1].reason, format!("Undone: {reason}"));
+        assert_eq!(log[1].score_after, 0);
+
+        ctx.redo_last_action().expect("Test");
+        assert_eq!(ctx.players[&1].stats.score, 300);
+        assert_eq!(ctx.get_score_adjustments().len(), 3);
+        assert_eq!(ctx.get_score_adjustments()[2].score_after, 300);
+    }
+}
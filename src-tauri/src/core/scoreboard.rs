@@ -0,0 +1,78 @@
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+use crate::api::dto::ScoreboardDto;
+use crate::api::mapper::map_game_to_scoreboard_dto;
+use crate::core::game_entities::GameContext;
+
+/// Scoreboards the web server keeps up with before they are dropped as lagging
+const SCOREBOARD_UPDATES_CAPACITY: usize = 16;
+
+/// Latest scoreboard and the live updates for the public scoreboard page.
+///
+/// Clones share the same feed: the game publishes, the web server subscribes
+#[derive(Debug, Clone)]
+pub struct ScoreboardFeed {
+    latest: Arc<Mutex<ScoreboardDto>>,
+    updates: broadcast::Sender<ScoreboardDto>,
+}
+
+impl Default for ScoreboardFeed {
+    fn default() -> Self {
+        let (updates, _) = broadcast::channel(SCOREBOARD_UPDATES_CAPACITY);
+        Self {
+            latest: Arc::new(Mutex::new(ScoreboardDto::default())),
+            updates,
+        }
+    }
+}
+
+impl ScoreboardFeed {
+    pub fn latest(&self) -> ScoreboardDto {
+        self.latest.lock().expect("Mutex is poisoned").clone()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ScoreboardDto> {
+        self.updates.subscribe()
+    }
+
+    pub fn publish(&self, scoreboard: ScoreboardDto) {
+        *self.latest.lock().expect("Mutex is poisoned") = scoreboard.clone();
+        // Nobody watches the scoreboard if there are no receivers
+        let _ = self.updates.send(scoreboard);
+    }
+}
+
+impl GameContext {
+    pub fn set_scoreboard(&mut self, feed: ScoreboardFeed) {
+        log::info!("Scoreboard is fed from the game");
+        feed.publish(map_game_to_scoreboard_dto(self));
+        self.scoreboard = Some(feed);
+    }
+
+    pub(crate) fn publish_scoreboard(&self) {
+        if let Some(feed) = &self.scoreboard {
+            feed.publish(map_game_to_scoreboard_dto(self));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::game_entities::Player;
+
+    #[test]
+    fn test_scoreboard_follows_the_game() {
+        let feed = ScoreboardFeed::default();
+        let mut ctx = GameContext::default();
+        ctx.players.insert(1, Player::new(1));
+        ctx.set_scoreboard(feed.clone());
+        let mut updates = feed.subscribe();
+
+        ctx.players.get_mut(&1).expect("Test").stats.score = 300;
+        ctx.publish_scoreboard();
+        assert_eq!(updates.try_recv().expect("Test").players[0].score, 300);
+        assert_eq!(feed.latest().players[0].score, 300);
+    }
+}
@@ -0,0 +1,247 @@
+use error_stack::{Report, Result, ResultExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::core::game_entities::{GameContext, GameplayError, Player, PlayerState, PlayerStats};
+use crate::hub_comm::hw::internal::api_types::TermButtonState::Pressed;
+use crate::hub_comm::hw::internal::api_types::TermEvent;
+
+/// Several terminals playing as one side.
+///
+/// The team takes part in the game as a single player with the team id: teammates share the
+/// score, the turn and the lockouts. Any teammate's press is the team's buzz
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Team {
+    pub id: u8,
+    pub name: String,
+    pub icon: String,
+    pub members: Vec<TeamMember>,
+}
+
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TeamMember {
+    pub term_id: u8,
+    pub name: String,
+    /// Answers given by the member on behalf of the team
+    pub stats: PlayerStats,
+}
+
+impl Team {
+    fn has_terminal(&self, term_id: u8) -> bool {
+        self.members.iter().any(|m| m.term_id == term_id)
+    }
+}
+
+impl GameContext {
+    /// Switches the game to team play: each team becomes a player with the team id.
+    /// Saving the players switches the game back to individual play
+    pub fn set_teams(&mut self, teams: Vec<Team>) -> Result<(), GameplayError> {
+        let mut team_ids = HashSet::new();
+        let mut term_ids = HashSet::new();
+        for team in &teams {
+            if team.id == 0 || !team_ids.insert(team.id) {
+                return Err(Report::new(GameplayError::OperationForbidden))
+                    .attach_printable(format!("Team id {} is not unique", team.id));
+            }
+            if team.members.is_empty() {
+                return Err(Report::new(GameplayError::OperationForbidden))
+                    .attach_printable(format!("Team {} has no terminals", team.name));
+            }
+            if let Some(member) = team.members.iter().find(|m| !term_ids.insert(m.term_id)) {
+                return Err(Report::new(GameplayError::OperationForbidden))
+                    .attach_printable(format!("Terminal {} is in several teams", member.term_id));
+            }
+        }
+
+        log::info!("Teams: {:#?}", teams);
+        self.players = teams
+            .iter()
+            .map(|team| {
+                let player = Player {
+                    name: team.name.clone(),
+                    icon: team.icon.clone(),
+                    term_id: team.id,
+                    is_used: true,
+                    state: PlayerState::Idle,
+                    stats: PlayerStats::default(),
+                };
+                (team.id, player)
+            })
+            .collect();
+        self.teams = teams.into_iter().map(|team| (team.id, team)).collect();
+        Ok(())
+    }
+
+    pub fn is_team_game(&self) -> bool {
+        !self.teams.is_empty()
+    }
+
+    /// Player the terminal plays for: the terminal itself or the team it belongs to
+    pub fn player_id_of_terminal(&self, term_id: u8) -> Option<u8> {
+        if !self.is_team_game() {
+            return Some(term_id);
+        }
+
+        self.teams
+            .values()
+            .find(|team| team.has_terminal(term_id))
+            .map(|team| team.id)
+    }
+
+    /// Terminals of the player: all teammates in team play
+    pub fn terminals_of_player(&self, player_id: u8) -> Vec<u8> {
+        match self.teams.get(&player_id) {
+            Some(team) => team.members.iter().map(|m| m.term_id).collect(),
+            None => vec![player_id],
+        }
+    }
+
+    /// Turns teammates' clicks into clicks of their teams. Does nothing in individual play
+    pub(crate) fn assign_clicks_to_teams(&self, events: Vec<TermEvent>) -> Vec<TermEvent> {
+        if !self.is_team_game() {
+            return events;
+        }
+
+        events
+            .into_iter()
+            .filter_map(|e| match self.player_id_of_terminal(e.term_id) {
+                Some(team_id) => Some(TermEvent {
+                    term_id: team_id,
+                    ..e
+                }),
+                None => {
+                    log::debug!(
+                        "Terminal {} is not in any team. Skipping: {:?}",
+                        e.term_id,
+                        e
+                    );
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Teammate whose press made the team's winning click
+    pub(crate) fn find_team_member_click(
+        &self,
+        presses: &[TermEvent],
+        team_click: &TermEvent,
+    ) -> Option<u8> {
+        let team = self.teams.get(&team_click.term_id)?;
+        presses
+            .iter()
+            .find(|e| {
+                e.state == Pressed
+                    && e.timestamp == team_click.timestamp
+                    && team.has_terminal(e.term_id)
+            })
+            .map(|e| e.term_id)
+    }
+
+    /// Rolls the judged answer up to the teammate who gave it
+    pub(crate) fn record_member_answer(&mut self, answered_correctly: bool, score_change: i32) {
+        let Some(term_id) = self.current.answering_term_id.take() else {
+            return;
+        };
+        let team_id = self.current.active_player_id();
+        let Some(member) = self
+            .teams
+            .get_mut(&team_id)
+            .and_then(|team| team.members.iter_mut().find(|m| m.term_id == term_id))
+        else {
+            log::error!("Terminal {} is not in team {}", term_id, team_id);
+            return;
+        };
+
+        member.stats.total_tries += 1;
+        member.stats.score += score_change;
+        if answered_correctly {
+            member.stats.correct_num += 1;
+        } else {
+            member.stats.wrong_num += 1;
+        }
+    }
+
+    pub(crate) fn record_member_false_start(&mut self, team_id: u8, term_id: u8) {
+        let Some(member) = self
+            .teams
+            .get_mut(&team_id)
+            .and_then(|team| team.members.iter_mut().find(|m| m.term_id == term_id))
+        else {
+            log::error!("Terminal {} is not in team {}", term_id, team_id);
+            return;
+        };
+        member.stats.false_start_num += 1;
+    }
+
+    pub fn fetch_teams(&self) -> Vec<Team> {
+        let mut teams: Vec<Team> = self.teams.values().cloned().collect();
+        teams.sort_by_key(|team| team.id);
+        teams
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_pack::pack_content_entities::test_pack::{make_pack, make_round, make_theme};
+    use crate::game_pack::pack_content_entities::QuestionId;
+    use crate::hub_comm::hw::hw_hub_manager::get_epoch_ms;
+    use crate::hub_comm::hw::internal::api_types::TermButtonState;
+
+    fn make_team(id: u8, term_ids: &[u8]) -> Team {
+        Team {
+            id,
+            name: format!("Team {}", id),
+            members: term_ids
+                .iter()
+                .map(|&term_id| TeamMember {
+                    term_id,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_teammate_press_is_team_buzz() {
+        let mut ctx = GameContext::default();
+        ctx.game_pack = make_pack(vec![make_round(vec![make_theme("Music", &[200])])]);
+        assert!(ctx
+            .set_teams(vec![make_team(1, &[11]), make_team(2, &[11])])
+            .is_err());
+        ctx.set_teams(vec![make_team(1, &[11, 12]), make_team(2, &[21])])
+            .expect("Test");
+
+        ctx.begin_game().expect("Test");
+        ctx.assign_first_chooser(2).expect("Test");
+        ctx.get_pack_question(QuestionId::default()).expect("Test");
+        let now = get_epoch_ms().expect("Test");
+        let base = now + 50;
+        ctx.allow_answer_from(base).expect("Test");
+
+        let press = |term_id, delay| TermEvent {
+            term_id,
+            timestamp: base + delay,
+            state: TermButtonState::Pressed,
+        };
+        let early_press = TermEvent {
+            timestamp: now,
+            ..press(11, 0)
+        };
+        assert!(ctx.arbitrate_clicks(vec![early_press]).is_none());
+        let winner = ctx
+            .arbitrate_clicks(vec![press(21, 30), press(12, 10), press(99, 5)])
+            .expect("Test");
+        assert_eq!(winner.playerId, 1);
+
+        ctx.answer_question(true).expect("Test");
+        assert_eq!(ctx.players[&1].stats.score, 200);
+        let member = &ctx.teams[&1].members[1];
+        assert_eq!((member.stats.correct_num, member.stats.score), (1, 200));
+        assert_eq!(ctx.players[&1].stats.false_start_num, 1);
+        assert_eq!(ctx.teams[&1].members[0].stats.false_start_num, 1);
+        assert_eq!(member.stats.false_start_num, 0);
+    }
+}
@@ -0,0 +1,280 @@
+use error_stack::{Report, Result, ResultExt};
+use serde::{Deserialize, Serialize};
+
+use crate::core::final_round::FinalRoundStage;
+use crate::core::game_entities::{GameContext, GameplayError, PlayerState};
+use crate::core::game_journal::JournalEvent;
+use crate::core::game_state_machine::GameCommand;
+use crate::core::round_timer::RoundTimer;
+use crate::game_pack::game_pack_entites::GamePack;
+use crate::game_pack::game_pack_loader::load_game_pack;
+use crate::game_pack::pack_content_entities::{Round, Theme};
+
+const TIE_BREAK_ROUND_NAME: &str = "Tie-break";
+const TIE_BREAK_ROUND_TYPE: &str = "tie-break";
+
+/// Where the tie-break questions come from
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum TieBreakSource {
+    /// Theme of the game pack reserved for the tie-break
+    PackTheme(String),
+    /// Separate pack at the given path: themes of its first round are played
+    Pack(String),
+}
+
+/// Sudden-death questions for the players tied for first once the last round is over.
+///
+/// Other players sit the tie-break out. It goes on until a single leader is left
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TieBreak {
+    pub source: TieBreakSource,
+    pub player_ids: Vec<u8>,
+    pub winner_id: Option<u8>,
+}
+
+impl GameContext {
+    /// Plays the tie-break round for the leaders. Returns the players taking part
+    pub fn start_tie_break(&mut self, source: TieBreakSource) -> Result<Vec<u8>, GameplayError> {
+        self.ensure_command_allowed(GameCommand::StartTieBreak)?;
+        if !self.is_last_round_over(&source) {
+            return Err(Report::new(GameplayError::OperationForbidden))
+                .attach_printable("Tie-break is played once the last round is over");
+        }
+
+        let player_ids = self.tied_leaders();
+        if player_ids.len() < 2 {
+            return Err(Report::new(GameplayError::OperationForbidden))
+                .attach_printable(format!("Nobody is tied for first: {:?}", player_ids));
+        }
+
+        let round = load_tie_break_round(&self.game_pack, &source)?;
+        log::info!(
+            "Starting tie-break for players {:?} with {:?}: {} questions",
+            player_ids,
+            source,
+            round.questions_left
+        );
+        self.journal.record(JournalEvent::TieBreakStarted {
+            source: source.clone(),
+        });
+
+        self.game_pack.content.rounds.push(round);
+        self.current.round_index = self.game_pack.content.rounds.len() - 1;
+        self.current.final_round = None;
+        self.current.round_timer = RoundTimer::start(None);
+        self.current.tie_break = Some(TieBreak {
+            source,
+            player_ids: player_ids.clone(),
+            winner_id: None,
+        });
+
+        self.deactivate_tie_break_spectators();
+        self.set_question_chooser(player_ids[0]);
+        Ok(player_ids)
+    }
+
+    pub fn get_tie_break(&self) -> Option<&TieBreak> {
+        self.current.tie_break.as_ref()
+    }
+
+    /// Whether the player waits for the tie-break to be over
+    pub(crate) fn sits_out_tie_break(&self, player_id: u8) -> bool {
+        match &self.current.tie_break {
+            Some(tie_break) => {
+                tie_break.winner_id.is_none() && !tie_break.player_ids.contains(&player_id)
+            }
+            None => false,
+        }
+    }
+
+    /// Players who are not tied for first sit the tie-break out
+    pub(crate) fn deactivate_tie_break_spectators(&mut self) {
+        let spectators: Vec<u8> = self
+            .players
+            .values()
+            .filter(|p| p.state != PlayerState::Dead && self.sits_out_tie_break(p.term_id))
+            .map(|p| p.term_id)
+            .collect();
+        for id in spectators {
+            if let Some(player) = self.players.get_mut(&id) {
+                player.state = PlayerState::Inactive;
+            }
+        }
+    }
+
+    /// Ends the tie-break once a single leader is left among its players.
+    /// Everyone else is back in the game
+    pub(crate) fn settle_tie_break(&mut self) {
+        let Some(tie_break) = &self.current.tie_break else {
+            return;
+        };
+        if tie_break.winner_id.is_some() {
+            return;
+        }
+
+        let scores: Vec<(u8, i32)> = tie_break
+            .player_ids
+            .iter()
+            .filter_map(|id| self.players.get(id))
+            .map(|p| (p.term_id, p.stats.score))
+            .collect();
+        let Some(top_score) = scores.iter().map(|&(_, score)| score).max() else {
+            return;
+        };
+        let leaders: Vec<u8> = scores
+            .iter()
+            .filter(|&&(_, score)| score == top_score)
+            .map(|&(id, _)| id)
+            .collect();
+        if leaders.len() != 1 {
+            return;
+        }
+
+        log::info!("Player {} wins the tie-break", leaders[0]);
+        if let Some(tie_break) = self.current.tie_break.as_mut() {
+            tie_break.winner_id = Some(leaders[0]);
+        }
+    }
+
+    /// Players in the game sharing the top score, by id
+    fn tied_leaders(&self) -> Vec<u8> {
+        let alive = self
+            .players
+            .values()
+            .filter(|p| p.state != PlayerState::Dead);
+        let Some(top_score) = alive.clone().map(|p| p.stats.score).max() else {
+            return vec![];
+        };
+
+        let mut leaders: Vec<u8> = alive
+            .filter(|p| p.stats.score == top_score)
+            .map(|p| p.term_id)
+            .collect();
+        leaders.sort();
+        leaders
+    }
+
+    /// Whether the last round is played out. Reserved tie-break theme doesn't count
+    fn is_last_round_over(&self, source: &TieBreakSource) -> bool {
+        if self.current.round_index + 1 < self.game_pack.content.rounds.len() {
+            return false;
+        }
+
+        if let Some(final_round) = &self.current.final_round {
+            return final_round.stage == FinalRoundStage::Finished;
+        }
+
+        let round = self.get_current_round();
+        let reserved = match source {
+            TieBreakSource::PackTheme(name) => round
+                .themes
+                .iter()
+                .filter(|theme| theme.name == *name)
+                .map(|theme| theme.remaining_questions().count() as i32)
+                .sum(),
+            TieBreakSource::Pack(_) => 0,
+        };
+        round.questions_left <= reserved || self.current.round_timer.is_expired()
+    }
+}
+
+/// Builds the round with the tie-break questions
+pub(crate) fn load_tie_break_round(
+    pack: &GamePack,
+    source: &TieBreakSource,
+) -> Result<Round, GameplayError> {
+    let themes: Vec<Theme> = match source {
+        TieBreakSource::PackTheme(name) => {
+            let theme = pack
+                .content
+                .rounds
+                .iter()
+                .flat_map(|round| &round.themes)
+                .find(|theme| theme.name == *name)
+                .ok_or(Report::new(GameplayError::PackElementNotPresent))
+                .attach_printable(format!("Can't find tie-break theme: {}", name))?;
+            vec![theme.clone()]
+        }
+        TieBreakSource::Pack(path) => {
+            // Tie-break pack is picked by the host for the few questions it needs
+            load_game_pack(path, true)
+                .change_context(GameplayError::PackElementNotPresent)
+                .attach_printable(format!("Can't load tie-break pack: {}", path))?
+                .content
+                .rounds
+                .into_iter()
+                .next()
+                .ok_or(Report::new(GameplayError::PackElementNotPresent))
+                .attach_printable(format!("Tie-break pack {} has no rounds", path))?
+                .themes
+        }
+    };
+
+    let question_count: i32 = themes
+        .iter()
+        .map(|t| t.remaining_questions().count() as i32)
+        .sum();
+    if question_count == 0 {
+        return Err(Report::new(GameplayError::PackElementNotPresent))
+            .attach_printable(format!("No tie-break questions in {:?}", source));
+    }
+
+    Ok(Round {
+        name: TIE_BREAK_ROUND_NAME.to_string(),
+        round_type: TIE_BREAK_ROUND_TYPE.to_string(),
+        themes,
+        question_count,
+        normal_question_count: question_count,
+        pip_question_count: 0,
+        questions_left: question_count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::game_entities::Player;
+    use crate::game_pack::pack_content_entities::test_pack::{make_round, make_theme};
+    use crate::game_pack::pack_content_entities::QuestionId;
+
+    #[test]
+    fn test_tie_break_until_single_leader() {
+        let mut ctx = GameContext::default();
+        let themes = [
+            make_theme("Music", &[100]),
+            make_theme("Reserve", &[300, 500]),
+        ];
+        ctx.game_pack
+            .content
+            .rounds
+            .push(make_round(Vec::from(themes)));
+        for (id, score) in [(1, 500), (2, 400), (3, 200)] {
+            let mut player = Player::new(id);
+            player.stats.score = score;
+            ctx.players.insert(id, player);
+        }
+        ctx.begin_game().expect("Test");
+        ctx.assign_first_chooser(2).expect("Test");
+
+        let source = TieBreakSource::PackTheme("Reserve".to_string());
+        ctx.get_pack_question(QuestionId::default()).expect("Test");
+        ctx.players.get_mut(&2).expect("Test").stats.score = 500;
+        ctx.finish_question_prematurely().expect("Test");
+        assert_eq!(
+            ctx.start_tie_break(source.clone()).expect("Test"),
+            vec![1, 2]
+        );
+        assert_eq!(ctx.players[&3].state, PlayerState::Inactive);
+        assert_eq!(ctx.get_question_chooser_id(), 1);
+
+        let id = QuestionId {
+            round: 1,
+            ..Default::default()
+        };
+        ctx.get_pack_question(id).expect("Test");
+        ctx.players.get_mut(&2).expect("Test").stats.score = 800;
+        ctx.finish_question_prematurely().expect("Test");
+        assert_eq!(ctx.get_tie_break().expect("Test").winner_id, Some(2));
+        assert_eq!(ctx.players[&3].state, PlayerState::Idle);
+    }
+}
@@ -23,6 +23,10 @@ pub struct PackLocationData {
 
 #[derive(Default, Debug, Clone)]
 pub struct GamePack {
+    /// Path to the pack archive the pack was loaded from
+    pub archive_path: String,
+    /// SHA-256 of the pack archive content as a hex string
+    pub archive_hash: String,
     pub location: PackLocationData,
     pub content: PackContent,
 }
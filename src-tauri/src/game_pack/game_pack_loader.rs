@@ -1,8 +1,11 @@
 use crate::game_pack::game_pack_entites::*;
 use crate::game_pack::pack_content_loader::load_pack_content;
+use crate::game_pack::pack_lint::{lint_pack, PackIssue, PackLintReport};
 use error_stack::{IntoReport, Report, Result, ResultExt};
 use serde::Serialize;
+use sha2::{Digest, Sha256};
 use std::error::Error;
+use std::io::{self, BufReader};
 use std::path::{Path};
 use std::sync::Arc;
 use std::{fmt, fs};
@@ -18,6 +21,8 @@ pub enum GamePackLoadingError {
     InvalidPathToPack(String),
     InvalidPackFileExtension(String),
     CorruptedPack(String),
+    /// Pack has warnings the host didn't agree to play with
+    WarningsNotAccepted(String),
     InternalError,
 }
 
@@ -29,9 +34,50 @@ impl fmt::Display for GamePackLoadingError {
 
 impl Error for GamePackLoadingError {}
 
-/// Accepts path to pack, returns Result with GamePack or GamePackLoadingError
-pub fn load_game_pack(game_archive_path: &str) -> Result<GamePack, GamePackLoadingError> {
+/// Accepts path to pack, returns Result with GamePack or GamePackLoadingError.
+/// Packs with lint errors aren't loaded. Packs with warnings are loaded once the host accepts them
+pub fn load_game_pack(
+    game_archive_path: &str,
+    accept_warnings: bool,
+) -> Result<GamePack, GamePackLoadingError> {
+    let pack = unpack_game_pack(game_archive_path)?;
+    let report = lint_pack(&pack)?;
+    report.issues.iter().for_each(|issue| log::warn!("{}", issue));
+    if report.issues.is_empty() || (accept_warnings && !report.has_errors()) {
+        return Ok(pack);
+    }
+
+    if report.has_errors() {
+        let err_message = format!("Pack {game_archive_path} has errors");
+        let error = Report::new(GamePackLoadingError::CorruptedPack(err_message.clone()))
+            .attach_printable(err_message);
+        return Err(attach_issues(error, report.errors()));
+    }
+
+    let err_message = format!("Pack {game_archive_path} has warnings");
+    let error = Report::new(GamePackLoadingError::WarningsNotAccepted(err_message.clone()))
+        .attach_printable(err_message);
+    Err(attach_issues(error, report.issues.iter()))
+}
+
+fn attach_issues<'a>(
+    error: Report<GamePackLoadingError>,
+    issues: impl Iterator<Item = &'a PackIssue>,
+) -> Report<GamePackLoadingError> {
+    issues.fold(error, |error, issue| {
+        error.attach_printable(issue.to_string())
+    })
+}
+
+/// Lists every problem of the pack without loading it into the game
+pub fn validate_game_pack(game_archive_path: &str) -> Result<PackLintReport, GamePackLoadingError> {
+    let pack = unpack_game_pack(game_archive_path)?;
+    lint_pack(&pack)
+}
+
+fn unpack_game_pack(game_archive_path: &str) -> Result<GamePack, GamePackLoadingError> {
     validate_pack_path(game_archive_path)?;
+    let archive_hash = hash_pack_archive(game_archive_path)?;
 
     let temp_dir = create_temp_directory()
         .change_context(GamePackLoadingError::InternalError)
@@ -60,6 +106,8 @@ pub fn load_game_pack(game_archive_path: &str) -> Result<GamePack, GamePackLoadi
         .attach_printable(err_message)?;
 
     Ok(GamePack {
+        archive_path: game_archive_path.to_string(),
+        archive_hash,
         location: locations,
         content: game_package,
     })
@@ -180,6 +228,23 @@ fn create_temp_directory() -> Result<Arc<TempDir>, GamePackLoadingError> {
     Ok(temp_dir)
 }
 
+/// SHA-256 of the archive. Stays the same across app and toolchain updates,
+/// so saved games and journals can be matched against the pack
+fn hash_pack_archive(archive_path: &str) -> Result<String, GamePackLoadingError> {
+    let file = fs::File::open(archive_path)
+        .into_report()
+        .change_context(GamePackLoadingError::InternalError)
+        .attach_printable(format!("Can't open pack archive {archive_path:?}"))?;
+
+    let mut reader = BufReader::new(file);
+    let mut hasher = Sha256::new();
+    io::copy(&mut reader, &mut hasher)
+        .into_report()
+        .change_context(GamePackLoadingError::InternalError)
+        .attach_printable(format!("Can't read pack archive {archive_path:?}"))?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
 fn unarchive_zip(archive_path: &str, directory_path: &str) -> Result<(), GamePackLoadingError> {
     let file = fs::File::open(archive_path)
         .into_report()
@@ -9,6 +9,9 @@ pub enum AtomTypeDto {
     video,
     marker,
     image,
+    /// Shown as text. Reported by the pack lint
+    #[serde(other)]
+    unknown,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
@@ -26,50 +29,211 @@ pub struct ScenarioDto {
     pub atoms_list: Vec<AtomDto>,
 }
 
+/// Answer variants of the `<right>` and `<wrong>` elements
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct RightDto {
-    pub answer: String,
+    #[serde(default = "Vec::default")]
+    pub answer: Vec<String>,
+}
+
+impl RightDto {
+    pub fn first_answer(&self) -> String {
+        self.answer.first().cloned().unwrap_or_default()
+    }
+
+    /// Answers without the empty `<answer/>` elements
+    pub fn answers(&self) -> Vec<String> {
+        self.answer
+            .iter()
+            .map(|a| a.trim())
+            .filter(|a| !a.is_empty())
+            .map(str::to_owned)
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub struct SourcesDto {
+    #[serde(default = "Vec::default")]
+    pub source: Vec<String>,
+}
+
+/// Notes for the host attached to the question
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub struct QuestionInfoDto {
+    #[serde(default = "String::default")]
+    pub comments: String,
+    #[serde(default = "SourcesDto::default")]
+    pub sources: SourcesDto,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct QuestionParamDto {
+    pub name: String,
+    #[serde(default = "String::default")]
+    #[serde(rename = "$value")]
+    pub value: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct QuestionTypeDto {
+    pub name: String,
+    #[serde(default = "Vec::default")]
+    pub param: Vec<QuestionParamDto>,
+}
+
+impl QuestionTypeDto {
+    pub fn param_value(&self, name: &str) -> Option<&str> {
+        self.param
+            .iter()
+            .find(|p| p.name == name)
+            .map(|p| p.value.trim())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct QuestionDto {
+    #[serde(default = "Option::default")]
+    pub r#type: Option<QuestionTypeDto>,
     pub scenario: ScenarioDto,
     pub right: RightDto,
+    #[serde(default = "Option::default")]
+    pub wrong: Option<RightDto>,
+    #[serde(default = "Option::default")]
+    pub info: Option<QuestionInfoDto>,
     pub price: i32,
 }
 
+// Questions of the v5 schema
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
-pub struct QuestionsDto {
+pub struct ItemDto {
+    #[serde(default = "String::default")]
+    pub r#type: String,
+    #[serde(default = "String::default")]
+    #[serde(rename = "isRef")]
+    pub is_ref: String,
+    #[serde(default = "default_atom_content")]
     #[serde(rename = "$value")]
-    pub questions_list: Vec<QuestionDto>,
+    pub content: String,
+}
+
+impl ItemDto {
+    /// Whether the content is a file of the pack rather than the text or the link
+    pub fn is_ref(&self) -> bool {
+        self.is_ref.eq_ignore_ascii_case("true")
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct NumberSetDto {
+    pub minimum: String,
+    pub maximum: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum ParamValueDto {
+    Text(String),
+    NumberSet(NumberSetDto),
+    Item(ItemDto),
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
-pub struct ThemeDto {
+pub struct ParamDto {
     pub name: String,
-    pub questions: QuestionsDto,
+    #[serde(default = "String::default")]
+    pub r#type: String,
+    #[serde(default = "Vec::default")]
+    #[serde(rename = "$value")]
+    pub values: Vec<ParamValueDto>,
+}
+
+impl ParamDto {
+    pub fn text(&self) -> Option<&str> {
+        self.values.iter().find_map(|v| match v {
+            ParamValueDto::Text(text) => Some(text.trim()),
+            _ => None,
+        })
+    }
+
+    pub fn number_set(&self) -> Option<&NumberSetDto> {
+        self.values.iter().find_map(|v| match v {
+            ParamValueDto::NumberSet(number_set) => Some(number_set),
+            _ => None,
+        })
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = &ItemDto> {
+        self.values.iter().filter_map(|v| match v {
+            ParamValueDto::Item(item) => Some(item),
+            _ => None,
+        })
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
-pub struct ThemesDto {
+pub struct ParamsDto {
     #[serde(rename = "$value")]
-    pub themes_list: Vec<ThemeDto>,
+    pub params_list: Vec<ParamDto>,
 }
 
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct QuestionV5Dto {
+    /// Kind of the question: `simple`, `secret`, `stake`, etc. Simple when absent
+    #[serde(default = "String::default")]
+    pub r#type: String,
+    #[serde(default = "Option::default")]
+    pub params: Option<ParamsDto>,
+    pub right: RightDto,
+    #[serde(default = "Option::default")]
+    pub wrong: Option<RightDto>,
+    #[serde(default = "Option::default")]
+    pub info: Option<QuestionInfoDto>,
+    pub price: i32,
+}
 
+impl QuestionV5Dto {
+    pub fn param(&self, name: &str) -> Option<&ParamDto> {
+        self.params
+            .as_ref()
+            .and_then(|params| params.params_list.iter().find(|p| p.name == name))
+    }
+}
+
+// Pack structure shared by the schemas
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
-pub struct RoundDto {
+pub struct QuestionsDto<Q> {
+    #[serde(default = "Vec::default")]
+    #[serde(rename = "$value")]
+    pub questions_list: Vec<Q>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct ThemeDto<Q> {
+    pub name: String,
+    pub questions: QuestionsDto<Q>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct ThemesDto<Q> {
+    #[serde(rename = "$value")]
+    pub themes_list: Vec<ThemeDto<Q>>,
+}
+
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct RoundDto<Q> {
     pub name: String,
     #[serde(default = "String::default")]
     pub r#type: String,
     #[serde(rename = "$value")]
-    pub themes: ThemesDto,
+    pub themes: ThemesDto<Q>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
-pub struct RoundsDto {
+pub struct RoundsDto<Q> {
     #[serde(rename = "$value")]
-    pub rounds_list: Vec<RoundDto>,
+    pub rounds_list: Vec<RoundDto<Q>>,
 }
 
 // Pack information
@@ -85,8 +249,62 @@ pub struct InfoDto {
     pub authors: Vec<AuthorDto>,
 }
 
+/// Layout of the questions in the pack content file
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PackSchema {
+    /// `<scenario>` of `<atom>`s, question type in the `<type>` element
+    V4,
+    /// `<params>` with `<item>`s, question type in the `type` attribute
+    V5,
+}
+
+// Outline of the v4 questions for the pack lint. The v5 questions keep the item types as is
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct AtomOutlineDto {
+    #[serde(default = "String::default")]
+    pub r#type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct ScenarioOutlineDto {
+    #[serde(default = "Vec::default")]
+    #[serde(rename = "$value")]
+    pub atoms_list: Vec<AtomOutlineDto>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct QuestionOutlineDto {
+    pub price: i32,
+    #[serde(default = "Option::default")]
+    pub r#type: Option<QuestionTypeDto>,
+    pub scenario: ScenarioOutlineDto,
+}
+
+/// Questions of the pack content file in its schema
+#[derive(Debug, PartialEq, Clone)]
+pub enum PackageOutline {
+    V4(PackageDto<QuestionOutlineDto>),
+    V5(PackageDto<QuestionV5Dto>),
+}
+
+/// Just enough of the package to pick the schema before the content is parsed
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct PackageHeaderDto {
+    #[serde(default = "String::default")]
+    pub version: String,
+}
+
+impl PackageHeaderDto {
+    pub fn schema(&self) -> PackSchema {
+        match self.version.trim().parse::<f32>() {
+            Ok(version) if version >= 5.0 => PackSchema::V5,
+            _ => PackSchema::V4,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
-pub struct PackageDto {
+pub struct PackageDto<Q = QuestionDto> {
     pub name: String,
     pub version: String,
     pub id: String,
@@ -95,7 +313,7 @@ pub struct PackageDto {
     pub date: String,
     pub difficulty: u8,
     pub info: InfoDto,
-    pub rounds: RoundsDto,
+    pub rounds: RoundsDto<Q>,
 }
 
 fn default_atom_type() -> AtomTypeDto {
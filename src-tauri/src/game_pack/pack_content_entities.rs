@@ -1,6 +1,6 @@
 use crate::api::dto::QuestionType;
-use serde::Serialize;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 
 // Game entities
 #[derive(Debug, PartialEq, Clone, Serialize)]
@@ -18,35 +18,119 @@ pub struct Atom {
     pub content: String,
 }
 
+/// Whom the chooser may pass the "cat in a bag" question to
+#[derive(Debug, PartialEq, Clone, Copy, Default, Serialize)]
+pub enum PigInPokeSelf {
+    /// Only to another player
+    #[default]
+    Forbidden,
+    /// To anyone, the chooser included
+    Allowed,
+    /// The chooser keeps the question
+    Only,
+}
+
+/// When the players learn the theme and the price of the "cat in a bag" question
+#[derive(Debug, PartialEq, Clone, Copy, Default, Serialize)]
+pub enum PigInPokeKnows {
+    /// Before the question is passed
+    #[default]
+    Before,
+    /// Once the question is passed
+    After,
+    /// Not until the question is shown
+    Never,
+}
+
+/// Theme and price announced for a "cat in a bag" question. Both are optional in the pack:
+/// when absent, the question is played with its own theme and nominal price.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct PigInPokeParams {
+    pub theme: Option<String>,
+    pub price: Option<i32>,
+    pub self_pass: PigInPokeSelf,
+    pub knows: PigInPokeKnows,
+}
+
+/// What only the host sees while judging the answers
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct HostNotes {
+    /// Accepted answer variants, the right answer first
+    pub right_answers: Vec<String>,
+    /// Answers to be rejected explicitly
+    pub wrong_answers: Vec<String>,
+    pub comments: String,
+    pub sources: Vec<String>,
+}
+
 #[derive(Debug, PartialEq, Clone, Default)]
 pub struct Question {
     pub scenario: Vec<Atom>,
+    /// Answer illustration: atoms after the marker, shown once the question is closed
+    pub answer_scenario: Vec<Atom>,
     pub right_answer: String,
+    pub host_notes: HostNotes,
     pub question_type: QuestionType,
+    pub pip_params: Option<PigInPokeParams>,
     pub price: i32,
+    /// Played questions keep their place, so the ids of the others stay the same
+    pub played: bool,
+}
+
+impl Question {
+    /// Price the question is actually played for, taking the pig-in-poke override into account
+    pub fn play_price(&self) -> i32 {
+        self.pip_params
+            .as_ref()
+            .and_then(|p| p.price)
+            .unwrap_or(self.price)
+    }
+}
+
+/// Position of the question in the pack: indices of its round, theme and the question itself
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub struct QuestionId {
+    pub round: usize,
+    pub theme: usize,
+    pub question: usize,
+}
+
+impl fmt::Display for QuestionId {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{}:{}:{}", self.round, self.theme, self.question)
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Theme {
     pub name: String,
-    pub questions: HashMap<i32, Question>,
+    /// In the pack order. Several questions may share the price
+    pub questions: Vec<Question>,
 }
 
 impl Theme {
-    pub fn pop_question(&mut self, price: &i32) -> Option<Question> {
-        self.questions.remove(price)
+    pub fn pop_question(&mut self, index: usize) -> Option<Question> {
+        let question = self.questions.get_mut(index).filter(|q| !q.played)?;
+        question.played = true;
+        Some(question.clone())
+    }
+
+    pub fn get_question(&self, index: usize) -> Option<&Question> {
+        self.questions.get(index).filter(|q| !q.played)
     }
 
-    pub fn get_question(&self, price: &i32) -> Option<&Question> {
-        self.questions.get(price)
+    /// Questions not played yet with their indices in the theme
+    pub fn remaining_questions(&self) -> impl Iterator<Item = (usize, &Question)> {
+        self.questions.iter().enumerate().filter(|(_, q)| !q.played)
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Default, PartialEq, Clone)]
 pub struct Round {
     pub name: String,
     pub round_type: String,
-    pub themes: HashMap<String, Theme>,
+    /// In the pack order. Several themes may share the name
+    pub themes: Vec<Theme>,
     pub question_count: i32,
     pub normal_question_count: i32,
     pub pip_question_count: i32,
@@ -81,3 +165,47 @@ pub struct PackContent {
     pub info: Info,
     pub rounds: Vec<Round>,
 }
+
+/// Packs for the tests of the game logic
+#[cfg(test)]
+pub(crate) mod test_pack {
+    use super::*;
+    use crate::game_pack::game_pack_entites::GamePack;
+
+    /// Theme of normal questions of the given prices
+    pub(crate) fn make_theme(name: &str, prices: &[i32]) -> Theme {
+        let questions = prices
+            .iter()
+            .map(|&price| Question {
+                price,
+                ..Default::default()
+            })
+            .collect();
+        Theme {
+            name: name.to_string(),
+            questions,
+        }
+    }
+
+    /// Round with all questions of the themes left to play
+    pub(crate) fn make_round(themes: Vec<Theme>) -> Round {
+        let question_count = themes.iter().map(|t| t.questions.len() as i32).sum();
+        Round {
+            themes,
+            question_count,
+            normal_question_count: question_count,
+            questions_left: question_count,
+            ..Default::default()
+        }
+    }
+
+    /// Pack of the rounds. Archive hash is "42"
+    pub(crate) fn make_pack(rounds: Vec<Round>) -> GamePack {
+        let mut pack = GamePack {
+            archive_hash: "42".to_string(),
+            ..Default::default()
+        };
+        pack.content.rounds = rounds;
+        pack
+    }
+}
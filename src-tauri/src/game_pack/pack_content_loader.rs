@@ -2,7 +2,7 @@ use std::path::Path;
 #[allow(dead_code, unused, unused_imports)]
 use std::{collections::HashMap, error::Error, fmt, fs, io};
 
-use error_stack::{IntoReport, Result, ResultExt};
+use error_stack::{IntoReport, Report, Result, ResultExt};
 use serde_xml_rs::from_str;
 use unic_normal::StrNormalForm;
 use urlencoding::encode;
@@ -24,6 +24,12 @@ impl fmt::Display for ParsePackContentError {
 
 impl Error for ParsePackContentError {}
 
+/// Question type with the "cat in a bag" parameters, if it has them
+type QuestionTypeParams = (QuestionType, Option<PigInPokeParams>);
+
+/// Maps the question of either schema
+type QuestionMapper<Q> = fn(&Q) -> Result<Question, ParsePackContentError>;
+
 pub fn load_pack_content(
     pack_location_data: &PackLocationData,
 ) -> Result<PackContent, GamePackLoadingError> {
@@ -36,27 +42,75 @@ pub fn load_pack_content(
         .into_report()
         .attach_printable("Can't get content file path. Check pack location data validity")?;
 
-    let package: PackageDto = parse_package(package_content_file_str)
+    let mut mapped_content = parse_package(package_content_file_str)
         .change_context(GamePackLoadingError::CorruptedPack(
             "Can't parse package".to_string(),
         ))
         .attach_printable_lazy(|| "Can't load pack content: parsing failed".to_string())?;
 
-    let mut mapped_content = map_package(package);
-    expand_and_validate_package_paths(&mut mapped_content, pack_location_data)?;
+    expand_package_paths(&mut mapped_content, pack_location_data);
     Ok(mapped_content)
 }
 
-fn expand_and_validate_package_paths(
-    pack: &mut PackContent,
-    locations: &PackLocationData,
-) -> Result<(), GamePackLoadingError> {
-    let mut result = Ok(());
+/// Questions of the pack content file with their types and atom types, for the pack lint
+pub(crate) fn parse_package_outline(
+    content_file_path: &Path,
+) -> Result<PackageOutline, ParsePackContentError> {
+    let package_xml = fs::read_to_string(content_file_path)
+        .into_report()
+        .attach_printable_lazy(|| format!("Can't open package content file: {content_file_path:?}"))
+        .change_context(ParsePackContentError)?;
+
+    let header: PackageHeaderDto = from_str(&package_xml)
+        .into_report()
+        .attach_printable("Can't read package version")
+        .change_context(ParsePackContentError)?;
+
+    let outline = match header.schema() {
+        PackSchema::V4 => from_str(&package_xml).map(PackageOutline::V4),
+        PackSchema::V5 => from_str(&package_xml).map(PackageOutline::V5),
+    };
+    outline
+        .into_report()
+        .attach_printable("Can't read package outline")
+        .change_context(ParsePackContentError)
+}
+
+/// Game question type of the pack type name. Questions of unknown types are played
+/// as normal ones and reported by the pack lint
+pub(crate) fn question_type_of(schema: PackSchema, name: &str) -> Option<QuestionType> {
+    let question_type = match (schema, name) {
+        (PackSchema::V4, "simple") => QuestionType::Normal,
+        (PackSchema::V4, "cat" | "bagcat") => QuestionType::PigInPoke,
+        (PackSchema::V4, "auction") => QuestionType::Auction,
+        (PackSchema::V4, "sponsored") => QuestionType::Sponsored,
+        // Everybody answers the final round questions the same way
+        (PackSchema::V5, "" | "simple" | "forAll" | "stakeAll") => QuestionType::Normal,
+        (PackSchema::V5, "secret" | "secretPublicPrice" | "secretNoQuestion") => {
+            QuestionType::PigInPoke
+        }
+        (PackSchema::V5, "stake") => QuestionType::Auction,
+        (PackSchema::V5, "noRisk") => QuestionType::Sponsored,
+        _ => return None,
+    };
+    Some(question_type)
+}
 
+/// Known question type of the type name, normal one for the unknown name
+fn known_question_type(schema: PackSchema, name: &str) -> QuestionType {
+    question_type_of(schema, name).unwrap_or_else(|| {
+        log::warn!("Unknown question type '{name}' is played as a normal question");
+        QuestionType::Normal
+    })
+}
+
+/// Points media atoms to the unpacked files. Missing files are reported by the pack lint
+fn expand_package_paths(pack: &mut PackContent, locations: &PackLocationData) {
     pack.rounds.iter_mut().for_each(|r| {
-        r.themes.iter_mut().for_each(|(_, theme)| {
-            theme.questions.iter_mut().for_each(|(_, q)| {
-                q.scenario.iter_mut().for_each(|a| {
+        r.themes.iter_mut().for_each(|theme| {
+            theme.questions.iter_mut().for_each(|q| {
+                let atoms = q.scenario.iter_mut().chain(q.answer_scenario.iter_mut());
+                atoms.for_each(|a| {
                     log::debug!("Atom {:?} before mapping: {}", a.atom_type, a.content);
                     match a.atom_type {
                         QuestionMediaType::Say => {}
@@ -87,25 +141,13 @@ fn expand_and_validate_package_paths(
                         }
                     }
                     log::debug!("Atom {:?} after mapping: {}", a.atom_type, a.content);
-                    if is_atom_media(&a.atom_type) && !Path::new(&a.content).exists() {
-                        let err_msg = format!(
-                            "Atom corrupted! Round: {}, theme: {}, question: {}, atom {:?}",
-                            r.name, theme.name, q.price, a
-                        );
-                        log::error!("{}", err_msg);
-                        result = Err(GamePackLoadingError::CorruptedPack(err_msg.clone()))
-                            .into_report()
-                            .attach_printable(err_msg);
-                    }
                 })
             })
         })
     });
-
-    result
 }
 
-fn is_atom_media(qmt: &QuestionMediaType) -> bool {
+pub(crate) fn is_atom_media(qmt: &QuestionMediaType) -> bool {
     *qmt == QuestionMediaType::Image
         || *qmt == QuestionMediaType::Voice
         || *qmt == QuestionMediaType::Video
@@ -119,22 +161,48 @@ fn to_url_filename(a: &mut Atom) -> String {
     encode(&normalized_filename).to_string()
 }
 
-fn parse_package(file_path: &str) -> Result<PackageDto, ParsePackContentError> {
+fn parse_package(file_path: &str) -> Result<PackContent, ParsePackContentError> {
     let package_xml = fs::read_to_string(file_path)
         .into_report()
         .attach_printable_lazy(|| format!("Can't open package content file: '{file_path}'"))
         .change_context(ParsePackContentError)?;
 
-    let package_dto = from_str(&package_xml)
-        .into_report()
+    parse_package_xml(&package_xml)
         .attach_printable_lazy(|| format!("Can't parse pack content XML file: '{file_path}'"))
+}
+
+/// Parses the content of either schema into the same model
+fn parse_package_xml(package_xml: &str) -> Result<PackContent, ParsePackContentError> {
+    let header: PackageHeaderDto = from_str(package_xml)
+        .into_report()
+        .attach_printable("Can't read package version")
         .change_context(ParsePackContentError)?;
 
-    Ok(package_dto)
+    let schema = header.schema();
+    log::info!("Pack version {:?}, schema {:?}", header.version, schema);
+    let content = match schema {
+        PackSchema::V4 => {
+            let package_dto: PackageDto = from_str(package_xml)
+                .into_report()
+                .change_context(ParsePackContentError)?;
+            map_package(package_dto, map_question)?
+        }
+        PackSchema::V5 => {
+            let package_dto: PackageDto<QuestionV5Dto> = from_str(package_xml)
+                .into_report()
+                .change_context(ParsePackContentError)?;
+            map_package(package_dto, map_question_v5)?
+        }
+    };
+
+    Ok(content)
 }
 
-fn map_package(dto: PackageDto) -> PackContent {
-    PackContent {
+fn map_package<Q>(
+    dto: PackageDto<Q>,
+    map_question: QuestionMapper<Q>,
+) -> Result<PackContent, ParsePackContentError> {
+    Ok(PackContent {
         name: dto.name,
         version: dto.version,
         id: dto.id,
@@ -156,10 +224,10 @@ fn map_package(dto: PackageDto) -> PackContent {
             dto.rounds
                 .rounds_list
                 .iter()
-                .map(map_round)
-                .collect::<Vec<Round>>()
+                .map(|r| map_round(r, map_question))
+                .collect::<Result<Vec<Round>, ParsePackContentError>>()?
         },
-    }
+    })
 }
 
 fn map_atoms(a: &AtomDto) -> Atom {
@@ -171,68 +239,419 @@ fn map_atoms(a: &AtomDto) -> Atom {
                 AtomTypeDto::video => QuestionMediaType::Video,
                 AtomTypeDto::marker => QuestionMediaType::Marker,
                 AtomTypeDto::image => QuestionMediaType::Image,
+                AtomTypeDto::unknown => QuestionMediaType::Say,
             }
         },
         content: a.content.clone(),
     }
 }
 
-fn map_question(q: &QuestionDto) -> Question {
-    Question {
+fn map_question(q: &QuestionDto) -> Result<Question, ParsePackContentError> {
+    let (question_type, pip_params) =
+        map_question_type(q).attach_printable_lazy(|| format!("Price: {}", q.price))?;
+    let (scenario, answer_scenario) =
+        split_scenario(q.scenario.atoms_list.iter().map(map_atoms).collect());
+    Ok(Question {
         price: q.price,
-        scenario: {
-            q.scenario
-                .atoms_list
-                .iter()
-                .map(map_atoms)
-                .collect::<Vec<Atom>>()
-        },
-        right_answer: q.right.answer.clone(),
-        // TODO: Set random pip
-        question_type: QuestionType::Normal,
+        scenario,
+        answer_scenario,
+        right_answer: q.right.first_answer(),
+        host_notes: map_host_notes(&q.right, &q.wrong, &q.info),
+        question_type,
+        pip_params,
+        played: false,
+    })
+}
+
+/// Atoms after the marker illustrate the answer
+fn split_scenario(mut scenario: Vec<Atom>) -> (Vec<Atom>, Vec<Atom>) {
+    let marker = scenario
+        .iter()
+        .position(|a| a.atom_type == QuestionMediaType::Marker);
+    let Some(marker) = marker else {
+        return (scenario, vec![]);
+    };
+
+    let answer_scenario = scenario.split_off(marker + 1);
+    scenario.truncate(marker);
+    (scenario, answer_scenario)
+}
+
+fn map_question_type(q: &QuestionDto) -> Result<QuestionTypeParams, ParsePackContentError> {
+    let Some(type_dto) = &q.r#type else {
+        return Ok((QuestionType::Normal, None));
+    };
+
+    match known_question_type(PackSchema::V4, &type_dto.name) {
+        QuestionType::PigInPoke => {
+            let param = |name| type_dto.param_value(name);
+            let params = PigInPokeParams {
+                theme: param("theme")
+                    .filter(|theme| !theme.is_empty())
+                    .map(str::to_owned),
+                // Zero cost leaves the nominal price
+                price: parse_type_param("cost", param("cost"), |cost| cost.parse::<i32>().ok())?
+                    .filter(|&cost| cost > 0),
+                self_pass: parse_type_param("self", param("self"), parse_pip_self)?
+                    .unwrap_or_default(),
+                knows: parse_type_param("knows", param("knows"), parse_pip_knows)?
+                    .unwrap_or_default(),
+            };
+            Ok((QuestionType::PigInPoke, Some(params)))
+        }
+        question_type => Ok((question_type, None)),
     }
 }
 
-fn map_theme(t: &ThemeDto) -> (String, Theme) {
-    (
-        t.name.clone(),
-        Theme {
-            name: t.name.clone(),
-            questions: {
-                t.questions
-                    .questions_list
-                    .iter()
-                    .map(|q| (q.price, { map_question(q) }))
-                    .collect::<HashMap<i32, Question>>()
-            },
-        },
-    )
+fn parse_pip_self(value: &str) -> Option<PigInPokeSelf> {
+    match value {
+        "false" => Some(PigInPokeSelf::Forbidden),
+        "true" => Some(PigInPokeSelf::Allowed),
+        "only" => Some(PigInPokeSelf::Only),
+        _ => None,
+    }
 }
 
-fn map_round(r: &RoundDto) -> Round {
+fn parse_pip_knows(value: &str) -> Option<PigInPokeKnows> {
+    match value {
+        "before" => Some(PigInPokeKnows::Before),
+        "after" => Some(PigInPokeKnows::After),
+        "never" => Some(PigInPokeKnows::Never),
+        _ => None,
+    }
+}
+
+/// Typed value of the question type parameter. Absent or empty parameter has no value
+fn parse_type_param<T>(
+    name: &str,
+    value: Option<&str>,
+    parse: fn(&str) -> Option<T>,
+) -> Result<Option<T>, ParsePackContentError> {
+    let Some(value) = value.filter(|value| !value.is_empty()) else {
+        return Ok(None);
+    };
+
+    parse(value)
+        .map(Some)
+        .ok_or(Report::new(ParsePackContentError))
+        .attach_printable_lazy(|| format!("Invalid question parameter {}: '{}'", name, value))
+}
+
+fn map_question_v5(q: &QuestionV5Dto) -> Result<Question, ParsePackContentError> {
+    let items = |name| {
+        q.param(name)
+            .into_iter()
+            .flat_map(|p| p.items())
+            .map(map_item)
+            .collect::<Vec<Atom>>()
+    };
+
+    let (question_type, pip_params) =
+        map_question_type_v5(q).attach_printable_lazy(|| format!("Price: {}", q.price))?;
+    Ok(Question {
+        price: q.price,
+        scenario: items("question"),
+        answer_scenario: items("answer"),
+        right_answer: q.right.first_answer(),
+        host_notes: map_host_notes(&q.right, &q.wrong, &q.info),
+        question_type,
+        pip_params,
+        played: false,
+    })
+}
+
+fn map_host_notes(
+    right: &RightDto,
+    wrong: &Option<RightDto>,
+    info: &Option<QuestionInfoDto>,
+) -> HostNotes {
+    let info = info.clone().unwrap_or_default();
+    HostNotes {
+        right_answers: right.answers(),
+        wrong_answers: wrong.as_ref().map(RightDto::answers).unwrap_or_default(),
+        comments: info.comments.trim().to_owned(),
+        sources: info
+            .sources
+            .source
+            .iter()
+            .map(|s| s.trim().to_owned())
+            .collect(),
+    }
+}
+
+fn map_item(item: &ItemDto) -> Atom {
+    let atom_type = match item.r#type.as_str() {
+        "image" if item.is_ref() => QuestionMediaType::Image,
+        "audio" | "voice" if item.is_ref() => QuestionMediaType::Voice,
+        "video" if item.is_ref() => QuestionMediaType::Video,
+        _ => QuestionMediaType::Say,
+    };
+
+    // Media references are prefixed with '@' in the v4 scenario, paths are expanded the same way
+    let content = if atom_type == QuestionMediaType::Say {
+        item.content.clone()
+    } else {
+        format!("@{}", item.content.trim())
+    };
+
+    Atom { atom_type, content }
+}
+
+fn map_question_type_v5(q: &QuestionV5Dto) -> Result<QuestionTypeParams, ParsePackContentError> {
+    match known_question_type(PackSchema::V5, &q.r#type) {
+        QuestionType::PigInPoke => {
+            let params = PigInPokeParams {
+                theme: q
+                    .param("theme")
+                    .and_then(|p| p.text())
+                    .filter(|theme| !theme.is_empty())
+                    .map(str::to_owned),
+                // Only the fixed price is announced: the range is up to the player
+                price: q
+                    .param("price")
+                    .and_then(|p| p.number_set())
+                    .filter(|set| set.minimum == set.maximum)
+                    .and_then(|set| set.minimum.parse::<i32>().ok())
+                    .filter(|&price| price > 0),
+                self_pass: parse_type_param(
+                    "selectionMode",
+                    q.param("selectionMode").and_then(|p| p.text()),
+                    |value| match value {
+                        "exceptCurrent" => Some(PigInPokeSelf::Forbidden),
+                        "any" => Some(PigInPokeSelf::Allowed),
+                        _ => None,
+                    },
+                )?
+                .unwrap_or_default(),
+                knows: PigInPokeKnows::Before,
+            };
+            Ok((QuestionType::PigInPoke, Some(params)))
+        }
+        question_type => Ok((question_type, None)),
+    }
+}
+
+fn map_theme<Q>(
+    t: &ThemeDto<Q>,
+    map_question: QuestionMapper<Q>,
+) -> Result<Theme, ParsePackContentError> {
+    let questions = t
+        .questions
+        .questions_list
+        .iter()
+        .map(map_question)
+        .collect::<Result<Vec<Question>, ParsePackContentError>>()
+        .attach_printable_lazy(|| format!("Theme: {}", t.name))?;
+
+    Ok(Theme {
+        name: t.name.clone(),
+        questions,
+    })
+}
+
+fn map_round<Q>(
+    r: &RoundDto<Q>,
+    map_question: QuestionMapper<Q>,
+) -> Result<Round, ParsePackContentError> {
+    let themes = r
+        .themes
+        .themes_list
+        .iter()
+        .map(|t| map_theme(t, map_question))
+        .collect::<Result<Vec<Theme>, ParsePackContentError>>()
+        .attach_printable_lazy(|| format!("Round: {}", r.name))?;
+
     let mut round = Round {
         name: r.name.clone(),
         round_type: r.r#type.clone(),
-        themes: {
-            r.themes
-                .themes_list
-                .iter()
-                .map(map_theme)
-                .collect::<HashMap<String, Theme>>()
-        },
+        themes,
         questions_left: -1,
         question_count: -1,
         normal_question_count: -1,
         pip_question_count: -1,
     };
-    let vec = Vec::from_iter(round.themes.values());
-    round.question_count = vec
+    round.question_count = round
+        .themes
         .iter()
-        .map(|&theme| theme.questions.len() as i32)
+        .map(|theme| theme.questions.len() as i32)
         .sum::<i32>();
 
+    round.pip_question_count = round
+        .themes
+        .iter()
+        .flat_map(|theme| &theme.questions)
+        .filter(|&q| q.question_type == QuestionType::PigInPoke)
+        .count() as i32;
+
     round.questions_left = round.question_count;
-    round.normal_question_count = round.question_count;
-    round.pip_question_count = 0;
-    round
+    round.normal_question_count = round
+        .themes
+        .iter()
+        .flat_map(|theme| &theme.questions)
+        .filter(|&q| q.question_type == QuestionType::Normal)
+        .count() as i32;
+    Ok(round)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cat_question_mapping() {
+        let xml = r#"
+            <question price="200">
+                <type name="cat">
+                    <param name="theme">Hidden theme</param>
+                    <param name="cost">500</param>
+                </type>
+                <scenario><atom>Question text</atom></scenario>
+                <right><answer>Answer</answer></right>
+            </question>"#;
+        let dto: QuestionDto = from_str(xml).expect("Test");
+
+        let question = map_question(&dto).expect("Test");
+
+        assert_eq!(question.question_type, QuestionType::PigInPoke);
+        assert_eq!(question.price, 200);
+        assert_eq!(question.play_price(), 500);
+        assert_eq!(
+            question.pip_params,
+            Some(PigInPokeParams {
+                theme: Some("Hidden theme".to_string()),
+                price: Some(500),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_normal_question_mapping() {
+        let xml = r#"
+            <question price="100">
+                <scenario><atom>Question text</atom></scenario>
+                <right><answer>Answer</answer></right>
+            </question>"#;
+        let dto: QuestionDto = from_str(xml).expect("Test");
+
+        let question = map_question(&dto).expect("Test");
+
+        assert_eq!(question.question_type, QuestionType::Normal);
+        assert_eq!(question.pip_params, None);
+        assert_eq!(question.play_price(), 100);
+    }
+
+    #[test]
+    fn test_round_keeps_pack_order_and_duplicates() {
+        let question = |text: &str| {
+            format!(
+                r#"<question price="100">
+                    <scenario><atom>{text}</atom></scenario>
+                    <right><answer>Answer</answer></right>
+                </question>"#
+            )
+        };
+        let theme = |name: &str, questions: &[&str]| {
+            let questions: String = questions.iter().map(|q| question(q)).collect();
+            format!(r#"<theme name="{name}"><questions>{questions}</questions></theme>"#)
+        };
+        let xml = format!(
+            r#"<round name="Round 1"><themes>{}{}{}</themes></round>"#,
+            theme("Music", &["First", "Second"]),
+            theme("Art", &["Third"]),
+            theme("Music", &["Fourth"]),
+        );
+        let dto: RoundDto<QuestionDto> = from_str(&xml).expect("Test");
+
+        let round = map_round(&dto, map_question).expect("Test");
+
+        let names: Vec<&str> = round.themes.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["Music", "Art", "Music"]);
+        assert_eq!(round.question_count, 4);
+        assert_eq!(round.themes[0].questions[1].scenario[0].content, "Second");
+    }
+
+    #[test]
+    fn test_host_notes_mapping() {
+        let xml = r#"
+            <question price="100">
+                <info>
+                    <sources><source>Encyclopedia</source></sources>
+                    <comments>Either name counts</comments>
+                </info>
+                <scenario><atom>Question text</atom></scenario>
+                <right><answer>Tchaikovsky</answer><answer>Pyotr Ilyich</answer></right>
+                <wrong><answer>Rachmaninoff</answer></wrong>
+            </question>"#;
+        let dto: QuestionDto = from_str(xml).expect("Test");
+
+        let question = map_question(&dto).expect("Test");
+
+        assert_eq!(question.right_answer, "Tchaikovsky");
+        assert_eq!(
+            question.host_notes,
+            HostNotes {
+                right_answers: vec!["Tchaikovsky".to_string(), "Pyotr Ilyich".to_string()],
+                wrong_answers: vec!["Rachmaninoff".to_string()],
+                comments: "Either name counts".to_string(),
+                sources: vec!["Encyclopedia".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_question_type_params() {
+        let xml = r#"
+            <question price="300">
+                <type name="bagcat">
+                    <param name="cost">0</param>
+                    <param name="self">only</param>
+                    <param name="knows">never</param>
+                </type>
+                <scenario><atom>Question text</atom></scenario>
+                <right><answer>Answer</answer></right>
+            </question>"#;
+        let dto: QuestionDto = from_str(xml).expect("Test");
+
+        let question = map_question(&dto).expect("Test");
+
+        assert_eq!(question.play_price(), 300);
+        assert_eq!(
+            question.pip_params,
+            Some(PigInPokeParams {
+                theme: None,
+                price: None,
+                self_pass: PigInPokeSelf::Only,
+                knows: PigInPokeKnows::Never,
+            })
+        );
+
+        let invalid = xml.replace("never", "sometimes");
+        let dto: QuestionDto = from_str(&invalid).expect("Test");
+        assert!(map_question(&dto).is_err());
+
+        let unknown = xml.replace("bagcat", "riddle");
+        let dto: QuestionDto = from_str(&unknown).expect("Test");
+        let question = map_question(&dto).expect("Test");
+        assert_eq!(question.question_type, QuestionType::Normal);
+        assert_eq!(question.pip_params, None);
+    }
+
+    #[test]
+    fn test_both_schemas_map_to_same_content() {
+        let v4 = parse_package_xml(include_str!("../../tests/packs/content_v4.xml")).expect("Test");
+        let v5 = parse_package_xml(include_str!("../../tests/packs/content_v5.xml")).expect("Test");
+
+        assert_eq!(v4.rounds, v5.rounds);
+        let theme = &v5.rounds[0].themes[0];
+        assert_eq!(theme.name, "Music");
+        assert_eq!(theme.questions[0].scenario.len(), 2);
+        assert_eq!(theme.questions[0].scenario[1].content, "@melody.mp3");
+        assert_eq!(
+            theme.questions[0].answer_scenario[0].content,
+            "@composer.jpg"
+        );
+        assert_eq!(theme.questions[1].play_price(), 500);
+        assert_eq!(theme.questions[2].question_type, QuestionType::Auction);
+    }
 }
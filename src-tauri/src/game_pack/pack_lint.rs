@@ -0,0 +1,352 @@
+use error_stack::{Result, ResultExt};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+use crate::core::final_round::FINAL_ROUND_TYPE;
+use crate::game_pack::game_pack_entites::GamePack;
+use crate::game_pack::game_pack_loader::GamePackLoadingError;
+use crate::game_pack::pack_content_dto::{
+    PackSchema, PackageDto, PackageOutline, ParamDto, QuestionOutlineDto, QuestionV5Dto,
+};
+use crate::game_pack::pack_content_entities::{PackContent, Question};
+use crate::game_pack::pack_content_loader::{
+    is_atom_media, parse_package_outline, question_type_of,
+};
+
+const KNOWN_ATOM_TYPES: [&str; 5] = ["say", "voice", "video", "marker", "image"];
+const KNOWN_ITEM_TYPES: [&str; 5] = ["text", "image", "audio", "voice", "video"];
+
+/// Packs with errors can't be played. Warnings are up to the host
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PackIssueSeverity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackIssueKind {
+    /// Media file the atom refers to isn't in the pack
+    MissingMedia(String),
+    /// Atom of the type the game can't show. Shown as text
+    UnknownAtomType(String),
+    /// Question of the type the game can't play. Played as a normal question
+    UnknownQuestionType(String),
+    EmptyAnswer,
+    /// Several questions of the theme share the price: the board shows the same price twice
+    DuplicatePrice,
+    EmptyTheme,
+    NonPositivePrice,
+}
+
+impl PackIssueKind {
+    pub fn severity(&self) -> PackIssueSeverity {
+        match self {
+            PackIssueKind::MissingMedia(_) => PackIssueSeverity::Error,
+            PackIssueKind::UnknownAtomType(_)
+            | PackIssueKind::UnknownQuestionType(_)
+            | PackIssueKind::DuplicatePrice
+            | PackIssueKind::EmptyAnswer
+            | PackIssueKind::EmptyTheme
+            | PackIssueKind::NonPositivePrice => PackIssueSeverity::Warning,
+        }
+    }
+}
+
+impl fmt::Display for PackIssueKind {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackIssueKind::MissingMedia(path) => write!(fmt, "Missing media file: {path}"),
+            PackIssueKind::UnknownAtomType(atom_type) => {
+                write!(fmt, "Unknown atom type '{atom_type}' is shown as text")
+            }
+            PackIssueKind::UnknownQuestionType(question_type) => write!(
+                fmt,
+                "Unknown question type '{question_type}' is played as a normal question"
+            ),
+            PackIssueKind::EmptyAnswer => fmt.write_str("Question has no answer"),
+            PackIssueKind::DuplicatePrice => fmt.write_str("Several questions share the price"),
+            PackIssueKind::EmptyTheme => fmt.write_str("Theme has no questions"),
+            PackIssueKind::NonPositivePrice => fmt.write_str("Question price is not positive"),
+        }
+    }
+}
+
+/// Where the problem is in the pack
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackLocation {
+    pub round: String,
+    pub theme: String,
+    pub price: Option<i32>,
+}
+
+impl PackLocation {
+    fn new(round: &str, theme: &str, price: Option<i32>) -> Self {
+        Self {
+            round: round.to_owned(),
+            theme: theme.to_owned(),
+            price,
+        }
+    }
+}
+
+impl fmt::Display for PackLocation {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "Round: {}, theme: {}", self.round, self.theme)?;
+        match self.price {
+            Some(price) => write!(fmt, ", price: {price}"),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackIssue {
+    pub kind: PackIssueKind,
+    pub location: PackLocation,
+}
+
+impl PackIssue {
+    pub fn severity(&self) -> PackIssueSeverity {
+        self.kind.severity()
+    }
+}
+
+impl fmt::Display for PackIssue {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = self.severity();
+        write!(fmt, "{severity:?}: {}. {}", self.kind, self.location)
+    }
+}
+
+/// Every problem found in the pack
+#[derive(Debug, Clone, Default)]
+pub struct PackLintReport {
+    pub issues: Vec<PackIssue>,
+}
+
+impl PackLintReport {
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &PackIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity() == PackIssueSeverity::Error)
+    }
+
+    fn report(&mut self, kind: PackIssueKind, location: &PackLocation) {
+        self.issues.push(PackIssue {
+            kind,
+            location: location.clone(),
+        });
+    }
+}
+
+/// Checks the loaded pack. The pack content file is read again for what the content model drops
+pub fn lint_pack(pack: &GamePack) -> Result<PackLintReport, GamePackLoadingError> {
+    let content_file_path = &pack.location.content_file_path;
+    let outline = parse_package_outline(content_file_path).change_context(
+        GamePackLoadingError::CorruptedPack("Can't parse package".to_string()),
+    )?;
+
+    let mut report = PackLintReport::default();
+    match &outline {
+        PackageOutline::V4(package) => lint_outline(package, &mut report),
+        PackageOutline::V5(package) => lint_outline(package, &mut report),
+    }
+    lint_content(&pack.content, &mut report);
+    log::info!("Pack lint found {} issues", report.issues.len());
+    Ok(report)
+}
+
+/// Question of the pack content file as the schema has it
+trait QuestionOutline {
+    fn price(&self) -> i32;
+    fn outline_issues(&self) -> Vec<PackIssueKind>;
+}
+
+impl QuestionOutline for QuestionOutlineDto {
+    fn price(&self) -> i32 {
+        self.price
+    }
+
+    fn outline_issues(&self) -> Vec<PackIssueKind> {
+        let mut issues = vec![];
+        if let Some(type_dto) = &self.r#type {
+            if question_type_of(PackSchema::V4, &type_dto.name).is_none() {
+                issues.push(PackIssueKind::UnknownQuestionType(type_dto.name.clone()));
+            }
+        }
+
+        for atom in &self.scenario.atoms_list {
+            let atom_type = atom.r#type.as_str();
+            if !atom_type.is_empty() && !KNOWN_ATOM_TYPES.contains(&atom_type) {
+                issues.push(PackIssueKind::UnknownAtomType(atom_type.to_owned()));
+            }
+        }
+        issues
+    }
+}
+
+impl QuestionOutline for QuestionV5Dto {
+    fn price(&self) -> i32 {
+        self.price
+    }
+
+    fn outline_issues(&self) -> Vec<PackIssueKind> {
+        let mut issues = vec![];
+        if question_type_of(PackSchema::V5, &self.r#type).is_none() {
+            issues.push(PackIssueKind::UnknownQuestionType(self.r#type.clone()));
+        }
+
+        let items = self
+            .params
+            .iter()
+            .flat_map(|params| &params.params_list)
+            .flat_map(ParamDto::items);
+        for item in items {
+            let item_type = item.r#type.as_str();
+            if !item_type.is_empty() && !KNOWN_ITEM_TYPES.contains(&item_type) {
+                issues.push(PackIssueKind::UnknownAtomType(item_type.to_owned()));
+            }
+        }
+        issues
+    }
+}
+
+fn lint_outline<Q: QuestionOutline>(outline: &PackageDto<Q>, report: &mut PackLintReport) {
+    for round in &outline.rounds.rounds_list {
+        for theme in &round.themes.themes_list {
+            for question in &theme.questions.questions_list {
+                let location = PackLocation::new(&round.name, &theme.name, Some(question.price()));
+                for kind in question.outline_issues() {
+                    report.report(kind, &location);
+                }
+            }
+        }
+    }
+}
+
+fn lint_content(content: &PackContent, report: &mut PackLintReport) {
+    for round in &content.rounds {
+        for theme in &round.themes {
+            if theme.questions.is_empty() {
+                let location = PackLocation::new(&round.name, &theme.name, None);
+                report.report(PackIssueKind::EmptyTheme, &location);
+            }
+
+            let mut prices = HashSet::new();
+            for question in &theme.questions {
+                let location = PackLocation::new(&round.name, &theme.name, Some(question.price));
+                if !prices.insert(question.price) {
+                    report.report(PackIssueKind::DuplicatePrice, &location);
+                }
+                for kind in question_issues(question, &round.round_type) {
+                    report.report(kind, &location);
+                }
+            }
+        }
+    }
+}
+
+fn question_issues(question: &Question, round_type: &str) -> Vec<PackIssueKind> {
+    let mut issues = vec![];
+    // Final round questions are played for the wagers
+    if question.price <= 0 && round_type != FINAL_ROUND_TYPE {
+        issues.push(PackIssueKind::NonPositivePrice);
+    }
+    if question.right_answer.trim().is_empty() {
+        issues.push(PackIssueKind::EmptyAnswer);
+    }
+
+    let atoms = question.scenario.iter().chain(&question.answer_scenario);
+    for atom in atoms {
+        if is_atom_media(&atom.atom_type) && !Path::new(&atom.content).exists() {
+            issues.push(PackIssueKind::MissingMedia(atom.content.clone()));
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_pack::pack_content_entities::{Atom, QuestionMediaType};
+    use serde_xml_rs::from_str;
+
+    #[test]
+    fn test_question_issues_are_all_listed() {
+        let question = Question {
+            price: 0,
+            scenario: vec![
+                Atom {
+                    atom_type: QuestionMediaType::Image,
+                    content: "/missing/first.jpg".to_string(),
+                },
+                Atom {
+                    atom_type: QuestionMediaType::Voice,
+                    content: "/missing/second.mp3".to_string(),
+                },
+            ],
+            ..Default::default()
+        };
+
+        let issues = question_issues(&question, "");
+
+        assert_eq!(
+            issues,
+            vec![
+                PackIssueKind::NonPositivePrice,
+                PackIssueKind::EmptyAnswer,
+                PackIssueKind::MissingMedia("/missing/first.jpg".to_string()),
+                PackIssueKind::MissingMedia("/missing/second.mp3".to_string()),
+            ]
+        );
+        assert!(question_issues(&question, FINAL_ROUND_TYPE)
+            .iter()
+            .all(|kind| *kind != PackIssueKind::NonPositivePrice));
+    }
+    #[test]
+    fn test_unknown_types_of_both_schemas_are_reported() {
+        let v4 = r#"
+            <question price="100">
+                <type name="riddle" />
+                <scenario><atom type="sound">melody.mp3</atom><atom>Question text</atom></scenario>
+            </question>"#;
+        let question: QuestionOutlineDto = from_str(v4).expect("Test");
+        assert_eq!(
+            question.outline_issues(),
+            vec![
+                PackIssueKind::UnknownQuestionType("riddle".to_string()),
+                PackIssueKind::UnknownAtomType("sound".to_string()),
+            ]
+        );
+
+        let v5 = r#"
+            <question price="100" type="riddle">
+                <params>
+                    <param name="question" type="content">
+                        <item>Question text</item>
+                        <item type="html">page.html</item>
+                        <item type="audio" isRef="True">melody.mp3</item>
+                    </param>
+                </params>
+                <right><answer>Answer</answer></right>
+            </question>"#;
+        let question: QuestionV5Dto = from_str(v5).expect("Test");
+        assert_eq!(
+            question.outline_issues(),
+            vec![
+                PackIssueKind::UnknownQuestionType("riddle".to_string()),
+                PackIssueKind::UnknownAtomType("html".to_string()),
+            ]
+        );
+
+        let known = v5.replace("riddle", "stake").replace("html", "text");
+        let question: QuestionV5Dto = from_str(&known).expect("Test");
+        assert!(question.outline_issues().is_empty());
+    }
+}
@@ -1,6 +1,6 @@
 use std::str::FromStr;
-use std::{env, thread};
-use std::thread::{JoinHandle, sleep};
+use std::env;
+use std::thread::sleep;
 use std::time::Duration;
 use rgb::RGB8;
 
@@ -17,16 +17,16 @@ use crate::hub_comm::hw::internal::api_types::{TermButtonState, TermEvent};
 use crate::core::game_entities::{HubStatus, Player};
 use crate::hub_comm::web::web_server::internal_api::{TermFeedbackState, TermLightColorDto, TimestampDto};
 use crate::hub_comm::web::web_server::internal_api::INTERNAL_API::*;
-use crate::hub_comm::web::web_server::server;
 use crate::hub_comm::web::web_server::server::PlayerIdentityDto;
 
 const RETRY_INTERVAL_MS: u64 = 100;
 
+/// Buzzers on the players' phones. The hub only connects to the web server the app runs
+/// on start
 #[derive(Debug)]
 pub struct WebHubManager {
     pub base_url: Url,
     pub port: String,
-    pub server_handle: Option<JoinHandle<()>>,
     pub client: reqwest::Client,
     pub rt: Runtime,
 }
@@ -42,7 +42,6 @@ impl Default for WebHubManager {
         let manager = Self {
             port,
             base_url: Url::from_str(&endpoint).expect("Bad base url"),
-            server_handle: None,
             client: Default::default(),
             rt: Runtime::new().expect("No runtime - no game :D"),
         };
@@ -50,38 +49,6 @@ impl Default for WebHubManager {
     }
 }
 
-impl WebHubManager {
-    fn start_hub_server(&mut self) {
-        let handle = thread::spawn(move || {
-            server::main();
-        });
-        self.server_handle = Some(handle);
-    }
-}
-
-impl Drop for WebHubManager {
-    fn drop(&mut self) {
-        log::info!("--> Trying to drop WebHubManager <--");
-
-        if let Some(handle) = self.server_handle.take() {
-            let result = self.rt.block_on(async {
-                self.client
-                    .post("localhost:8888/shout")
-                    .send().await
-            }).into_report().change_context(HubManagerError::HttpCommunicationError);
-
-            match result {
-                Ok(_) => {
-                    handle.join().expect("Can't join thread");
-                }
-                Err(err) => {
-                    log::error!("Ну.. Прес F. Хз що робити. Err {:?}", err);
-                }
-            }
-        }
-    }
-}
-
 #[allow(dead_code, unused_variables)]
 impl HubManager for WebHubManager {
     fn get_hub_address(&self) -> String {
@@ -89,13 +56,7 @@ impl HubManager for WebHubManager {
     }
 
     fn probe(&mut self, _port: &str) -> Result<HubStatus, HubManagerError> {
-        if self.server_handle.is_some() {
-            log::debug!("Web HUB already initialized. Nothing to do");
-            self.get_hub_timestamp()?;
-            return Ok(HubStatus::Detected)
-        }
-
-        self.start_hub_server();
+        // Web server may still be starting
         for i in 0..50 {
             sleep(Duration::from_millis(RETRY_INTERVAL_MS));
             match self.get_hub_timestamp() {
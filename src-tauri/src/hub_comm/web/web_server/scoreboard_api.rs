@@ -0,0 +1,75 @@
+use rocket::fairing::AdHoc;
+use rocket::fs::NamedFile;
+use rocket::response::stream::{Event, EventStream};
+use rocket::response::Redirect;
+use rocket::serde::json::Json;
+use rocket::tokio::select;
+use rocket::tokio::sync::broadcast::error::RecvError;
+use rocket::{get, routes, Shutdown, State};
+
+use crate::api::dto::ScoreboardDto;
+use crate::core::scoreboard::ScoreboardFeed;
+
+/// Page for projectors and streaming PCs. Served from the static files
+#[get("/scoreboard")]
+fn scoreboard_page() -> Redirect {
+    Redirect::to("/scoreboard.html")
+}
+
+#[get("/scoreboard/state")]
+fn scoreboard_state(feed: &State<ScoreboardFeed>) -> Json<ScoreboardDto> {
+    Json(feed.latest())
+}
+
+/// Current scoreboard followed by every change of the game
+#[get("/scoreboard/events")]
+fn scoreboard_events(feed: &State<ScoreboardFeed>, mut shutdown: Shutdown) -> EventStream![] {
+    let mut updates = feed.subscribe();
+    let latest = feed.latest();
+
+    EventStream! {
+        yield Event::json(&latest);
+        loop {
+            let scoreboard = select! {
+                update = updates.recv() => match update {
+                    Ok(scoreboard) => scoreboard,
+                    Err(RecvError::Lagged(skipped)) => {
+                        log::debug!("Scoreboard viewer skipped {} updates", skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                _ = &mut shutdown => break,
+            };
+            yield Event::json(&scoreboard);
+        }
+    }
+}
+
+/// Player icons are files on the host, so the viewers get them through the server
+#[get("/scoreboard/icon/<player_id>")]
+async fn scoreboard_icon(player_id: i32, feed: &State<ScoreboardFeed>) -> Option<NamedFile> {
+    let icon_path = feed
+        .latest()
+        .players
+        .into_iter()
+        .find(|p| p.id == player_id)
+        .map(|p| p.playerIconPath)
+        .filter(|path| !path.is_empty())?;
+
+    NamedFile::open(icon_path).await.ok()
+}
+
+pub fn setup(feed: ScoreboardFeed) -> AdHoc {
+    AdHoc::on_ignite("Scoreboard-API", |rocket| async {
+        rocket.manage(feed).mount(
+            "/",
+            routes![
+                scoreboard_page,
+                scoreboard_state,
+                scoreboard_events,
+                scoreboard_icon
+            ],
+        )
+    })
+}
@@ -7,7 +7,8 @@ use std::sync::{Mutex};
 use rocket::form::FromForm;
 use rocket::fs::{FileServer, relative};
 use rocket::serde::{Deserialize, Serialize};
-use crate::hub_comm::web::web_server::{internal_api, player_api};
+use crate::core::scoreboard::ScoreboardFeed;
+use crate::hub_comm::web::web_server::{internal_api, player_api, scoreboard_api};
 
 pub type PlayerId = u8;
 
@@ -95,10 +96,17 @@ pub fn setup() -> rocket::fairing::AdHoc {
     })
 }
 
-#[rocket::launch]
-pub fn launch() -> Rocket<Build> {
+pub fn build(scoreboard: ScoreboardFeed) -> Rocket<Build> {
     rocket::build()
         .attach(setup())
         .attach(player_api::setup())
         .attach(internal_api::setup())
+        .attach(scoreboard_api::setup(scoreboard))
+}
+
+/// Runs the server on the current thread until it's shut down
+pub fn run(scoreboard: ScoreboardFeed) {
+    if let Err(e) = rocket::execute(build(scoreboard).launch()) {
+        log::error!("Web server stopped: {:?}", e);
+    }
 }
@@ -1,5 +1,6 @@
 pub mod api {
     pub mod dto;
+    pub mod events;
     pub mod mapper;
 
     pub mod controller {
@@ -9,8 +10,26 @@ pub mod api {
 }
 
 pub mod core {
+    pub mod answer_timer;
+    pub mod auction;
+    pub mod buzzer_arbiter;
+    pub mod false_start;
+    pub mod final_round;
+    pub mod game_engine;
     pub mod game_entities;
+    pub mod game_events;
+    pub mod game_history;
+    pub mod game_journal;
     pub mod game_logic;
+    pub mod game_save;
+    pub mod game_state_machine;
+    pub mod question_chooser;
+    pub mod round_timer;
+    pub mod rule_set;
+    pub mod score_adjustment;
+    pub mod scoreboard;
+    pub mod team;
+    pub mod tie_break;
 }
 
 pub mod game_pack {
@@ -19,6 +38,7 @@ pub mod game_pack {
     mod pack_content_dto;
     pub mod pack_content_entities;
     pub mod pack_content_loader;
+    pub mod pack_lint;
 }
 
 pub mod hub_comm {
@@ -41,6 +61,7 @@ pub mod hub_comm {
             pub mod server;
             pub mod internal_api;
             pub mod player_api;
+            pub mod scoreboard_api;
         }
     }
 }
@@ -7,14 +7,39 @@ use svoyak_tauri_app::api::controller::startup::hub::*;
 use svoyak_tauri_app::api::controller::startup::hw_hub::*;
 #[allow(unused_imports)]
 use svoyak_tauri_app::api::controller::startup::*;
-use svoyak_tauri_app::core::game_entities::*;
+use svoyak_tauri_app::api::events::TauriEventSink;
+use svoyak_tauri_app::core::game_engine::GameEngine;
+use svoyak_tauri_app::core::game_entities::GameContext;
+use svoyak_tauri_app::core::scoreboard::ScoreboardFeed;
+use svoyak_tauri_app::hub_comm::web::web_server::server;
+use std::thread;
+use tauri::Manager;
 
 fn main() {
     env_logger::init();
 
-    log_ctx_content();
+    // Games are saved and journaled in the app data dir, so they can be resumed and replayed
+    let data_dir = home::home_dir().map(|home| home.join(".svojak"));
+    if data_dir.is_none() {
+        log::error!("Can't find home dir. The game won't be saved");
+    }
+    let engine = GameEngine::new(GameContext::default(), data_dir);
+    log_ctx_content(&engine);
+
+    // The only web server of the app: serves the public scoreboard and the web hub buzzers
+    let scoreboard = ScoreboardFeed::default();
+    engine.context().set_scoreboard(scoreboard.clone());
+    thread::spawn(move || server::run(scoreboard));
 
     tauri::Builder::default()
+        .manage(engine)
+        .setup(|app| {
+            let engine = app.state::<GameEngine>();
+            engine
+                .context()
+                .set_event_sink(Box::new(TauriEventSink::new(app.handle())));
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             // Startup API
             set_hub_type,
@@ -23,10 +48,17 @@ fn main() {
             set_hub_radio_channel,
             discover_players,
             save_players,
+            save_teams,
+            fetch_teams,
+            validate_pack,
             get_pack_info,
             save_round_duration,
+            save_answer_duration,
+            save_false_start_penalty,
+            save_rule_set,
             get_pack_info,
             start_the_game,
+            resume_last_game,
             // Debug API
             setup_hub_connection,
             send_raw_request_frame,
@@ -35,16 +67,42 @@ fn main() {
             fetch_players,
             fetch_round,
             get_question_data,
+            reveal_answer_scene,
+            fetch_host_notes,
             allow_answer,
-            get_fastest_click,
+            fetch_answer_timer,
+            extend_answer_timer,
+            cancel_answer_timer,
             answer_question,
+            fetch_game_history,
+            undo_last_action,
+            redo_last_action,
             has_next_question,
             finish_question_prematurely,
             init_next_round,
             send_pip_victim,
+            fetch_auction_state,
+            place_auction_bid,
+            fetch_final_round,
+            eliminate_final_theme,
+            place_final_wager,
+            get_final_question,
+            submit_final_answer,
+            judge_final_answer,
             get_active_player_id,
+            get_question_chooser_id,
             is_allow_answer_required,
-            fetch_round_stats
+            fetch_round_stats,
+            fetch_game_stats,
+            adjust_player_score,
+            export_game_journal,
+            replay_game_journal,
+            fetch_game_state,
+            fetch_state_transitions,
+            fetch_round_timer,
+            set_round_timer_paused,
+            start_tie_break,
+            fetch_tie_break
         ])
         .run(tauri::generate_context!())
         .expect("Can't start Tauri app");
@@ -80,7 +138,7 @@ fn main() {
      */
 }
 
-fn log_ctx_content() {
-    let context = game();
+fn log_ctx_content(engine: &GameEngine) {
+    let context = engine.context();
     log::info!("default context: {context:#?}");
 }
\ No newline at end of file
@@ -2,12 +2,14 @@ const {convertFileSrc} = window.__TAURI__.tauri;
 
 import {
     allowAnswer,
-    answerQuestion, finishQuestionPrematurely,
+    answerQuestion, fetchHostNotes, finishQuestionPrematurely,
     getActivePlayerId,
     getQuestionData,
     hasNextQuestion,
     isAllowAnswerRequired,
-    waitForFirstClick
+    listenAnswerTimeExpired,
+    listenBuzzerWinner,
+    revealAnswerScene
 } from "../service/back-end-com.js";
 import {processPipPlayers} from "./modal/pig-in-poke-modal.js";
 import {processAuctionPlayers} from "./modal/auction-modal.js";
@@ -25,6 +27,7 @@ export async function processQuestionSelection(event) {
     const question = event.target;
     const price = question.innerText;
     const topic = question.querySelector("div").innerText;
+    const id = JSON.parse(question.dataset.questionId);
 
     console.info("Question selected: " + topic + ":" + price);
 
@@ -32,7 +35,7 @@ export async function processQuestionSelection(event) {
         return;
     }
 
-    await processQuestionDisplay(topic, price);
+    await processQuestionDisplay(id);
 
     question.className = "round-td-price used";
 }
@@ -56,6 +59,23 @@ function placeQuestionContent(question) {
     qCtx.slider = slider;
 }
 
+function placeHostNotes(notes) {
+    const lines = [];
+    if (notes.rightAnswers.length > 1) {
+        lines.push("Also accepted: " + notes.rightAnswers.slice(1).join("; "));
+    }
+    if (notes.wrongAnswers.length > 0) {
+        lines.push("Rejected: " + notes.wrongAnswers.join("; "));
+    }
+    if (notes.comments !== "") {
+        lines.push("Comments: " + notes.comments);
+    }
+    if (notes.sources.length > 0) {
+        lines.push("Sources: " + notes.sources.join("; "));
+    }
+    document.querySelector("#question-host-notes").innerText = lines.join("\n");
+}
+
 class Slider {
     constructor(scenario, questionViewport, answer) {
         this.scenario = scenario;
@@ -85,7 +105,7 @@ class Slider {
         }
     }
 
-    showAnswer() {
+    showAnswer(answerScene) {
         this.questionViewport.innerHTML = "";
 
         const text = document.createElement("p");
@@ -98,6 +118,7 @@ class Slider {
         colDiv.style.justifyContent = "center";
         colDiv.style.alignItems = "center";
         this.questionViewport.appendChild(colDiv);
+        answerScene.forEach((slide) => colDiv.appendChild(createSlideElement(slide)));
         colDiv.appendChild(text);
         let toRound = document.createElement("button");
         toRound.addEventListener("click", displayRoundScreen);
@@ -154,43 +175,8 @@ class Slider {
         const slide = this.scenario[index];
         console.log("Processing scenario: " + slide.mediaType + ":" + slide.content);
 
-        if (slide.mediaType === "Say") {
-            const text = document.createElement("p");
-            text.innerText = slide.content;
-            text.className = "question-text";
-            this.questionViewport.appendChild(text);
-            slide.element = text;
-        } else if (slide.mediaType === "Voice") {
-            const audio = document.createElement("audio");
-            audio.src = convertFileSrc(slide.content);
-            audio.controls = true;
-            audio.className = "question-audio";
-            this.questionViewport.appendChild(audio);
-            slide.element = audio;
-        } else if (slide.mediaType === "Video") {
-            const video = document.createElement("video");
-            video.src = convertFileSrc(slide.content);
-            video.controls = true;
-            video.className = "question-video";
-            this.questionViewport.appendChild(video);
-            slide.element = video;
-        } else if (slide.mediaType === "Image") {
-            const image = document.createElement("img");
-            image.src = convertFileSrc(slide.content);
-            image.alt = "Question Image";
-            image.className = "question-image";
-            this.questionViewport.appendChild(image);
-            slide.element = image;
-        } else {
-            console.log("Not supported format");
-
-            // Display content as text anyway
-            const element = document.createElement("p");
-            element.innerText = slide.content;
-            element.className = "question-text";
-            this.questionViewport.appendChild(element);
-            slide.element = element;
-        }
+        slide.element = createSlideElement(slide);
+        this.questionViewport.appendChild(slide.element);
 
         this.currentSlide = slide; // Update the current slide reference
     }
@@ -212,6 +198,41 @@ class Slider {
     }
 }
 
+function createSlideElement(slide) {
+    if (slide.mediaType === "Say") {
+        const text = document.createElement("p");
+        text.innerText = slide.content;
+        text.className = "question-text";
+        return text;
+    } else if (slide.mediaType === "Voice") {
+        const audio = document.createElement("audio");
+        audio.src = convertFileSrc(slide.content);
+        audio.controls = true;
+        audio.className = "question-audio";
+        return audio;
+    } else if (slide.mediaType === "Video") {
+        const video = document.createElement("video");
+        video.src = convertFileSrc(slide.content);
+        video.controls = true;
+        video.className = "question-video";
+        return video;
+    } else if (slide.mediaType === "Image") {
+        const image = document.createElement("img");
+        image.src = convertFileSrc(slide.content);
+        image.alt = "Question Image";
+        image.className = "question-image";
+        return image;
+    }
+
+    console.log("Not supported format");
+
+    // Display content as text anyway
+    const element = document.createElement("p");
+    element.innerText = slide.content;
+    element.className = "question-text";
+    return element;
+}
+
 
 async function setAnswerButtonsAccordingToQuestionType() {
     // Disable allow button
@@ -227,9 +248,9 @@ async function setAnswerButtonsAccordingToQuestionType() {
     }
 }
 
-export async function processQuestionDisplay(topic, price) {
-    console.log("Retreiving question '" + topic + ":" + price + "'");
-    const question = await getQuestionData(topic, parseInt(price));
+export async function processQuestionDisplay(id) {
+    console.log("Retreiving question " + JSON.stringify(id));
+    const question = await getQuestionData(id);
     console.log(
         "Response" +
         ". questionType: " +
@@ -247,8 +268,9 @@ export async function processQuestionDisplay(topic, price) {
     await setAnswerButtonsAccordingToQuestionType();
 
     placeQuestionContent(question);
+    placeHostNotes(await fetchHostNotes());
 
-    if (question.questionType === "Normal") {
+    if (question.questionType === "Normal" || question.questionType === "Sponsored") {
         displayQuestionScreen();
     } else if (question.questionType === "PigInPoke") {
         processPipPlayers(await getActivePlayerId());
@@ -307,13 +329,26 @@ export async function goToRoundScreen() {
 }
 
 export async function allowAnswerHandler() {
-    allowAnswer();
-    await waitForFirstClick();
+    await allowAnswer();
+}
+
+listenBuzzerWinner((winner) => {
+    console.log("Buzzer winner: " + winner.playerId + " in " + winner.reactionMs + " ms");
     updatePlayers();
     // setActivePlayerBadgeState("first-response");
     document.querySelector("#correct-answer-btn").className = "";
     document.querySelector("#wrong-answer-btn").className = "";
-}
+});
+
+listenAnswerTimeExpired(async (expired) => {
+    console.log("Player " + expired.playerId + " ran out of time. Answer is wrong");
+    await setAnswerButtonsAccordingToQuestionType();
+    updatePlayers();
+
+    if (!expired.retry) {
+        goToRoundScreen();
+    }
+});
 
 export function updateUserScore(responcePlayer) {
     const playerBadges = document.querySelector("#player-list").querySelectorAll(".player-badge");
@@ -352,8 +387,12 @@ export function updatePlayers() {
     displayPlayers();
 }
 
-function showAnswerModal() {
-    qCtx.slider.showAnswer()
+async function showAnswerModal() {
+    const answerScene = await revealAnswerScene().catch((err) => {
+        console.error("Can't reveal answer scene: " + err);
+        return [];
+    });
+    qCtx.slider.showAnswer(answerScene)
 }
 
 export async function processShowAnswer() {
@@ -22,6 +22,7 @@ export async function processPipPlayers(activePlayerId) {
  
         let playerBadge = document.createElement("div");
         playerBadge.className = "player-victim-badge";
+        playerBadge.dataset.playerId = player.id;
         playerBadge.addEventListener("click", processVictimSelection);
         playerBadge.style.cursor = "pointer";
         playerList.appendChild(playerBadge);
@@ -46,12 +47,11 @@ export async function processPipPlayers(activePlayerId) {
 }
 
 async function processVictimSelection(event) { 
-    // Єбаний костиль.
-    const victim = event.target.parentNode.parentNode;
+    const victim = event.currentTarget;
     const name = victim.querySelector("p").innerText;
     console.log("Victim is: " + name);
     
-    await sendPipVictim(name);
+    await sendPipVictim(parseInt(victim.dataset.playerId));
 
     const modal = document.querySelector("#pig-in-poke-modal");
     closeModal(modal);
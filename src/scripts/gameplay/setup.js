@@ -1,4 +1,4 @@
-import {fetchPlayers, fetchRound} from "../service/back-end-com.js";
+import {fetchPlayers, fetchRound, listenPlayersChanged} from "../service/back-end-com.js";
 import {closeModal, openModal} from "../service/modal-common.js";
 import {getImagePathOrDefault} from "../service/utils.js";
 import {
@@ -16,6 +16,7 @@ window.addEventListener("DOMContentLoaded", () => {
     addButtonEventListeners();
     displayPlayers();
     loadRoundFromBackend();
+    listenPlayersChanged(displayPlayers);
 });
 
 function addButtonEventListeners() {
@@ -66,8 +67,8 @@ function closeExitDialogModal() {
     closeModal(modal);
 }
 
-export async function displayPlayers() {
-    const players = await fetchPlayers();
+export async function displayPlayers(changedPlayers) {
+    const players = changedPlayers ?? await fetchPlayers();
     const playerList = document.querySelector("#player-list");
     playerList.innerHTML = "";
 
@@ -173,16 +174,17 @@ export async function loadRoundFromBackend() {
         ////////// 1-5 questions //////////
         let questionWidth = 65 / topic.questions.length;
         topic.questions.forEach((question) => {
-            addQuestion(question.price, topicMarker, tr, questionWidth);
+            addQuestion(question, topicMarker, tr, questionWidth);
         })
     });
 }
 
-function addQuestion(price, marker, tr, questionWidth) {
+function addQuestion(question, marker, tr, questionWidth) {
     let tdQuestion = document.createElement("td");
     tdQuestion.className = "round-td-price";
     tdQuestion.style.width = `${questionWidth}%`;
-    tdQuestion.innerText = price;
+    tdQuestion.innerText = question.price;
+    tdQuestion.dataset.questionId = JSON.stringify(question.id);
     tdQuestion.appendChild(marker.cloneNode(true));
     tdQuestion.addEventListener("click", processQuestionSelection);
     tr.appendChild(tdQuestion);
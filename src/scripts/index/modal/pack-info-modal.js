@@ -1,8 +1,8 @@
 import {openModal, closeModal} from "../../service/modal-common.js";
-import {getPackInfo, saveRoundDuration, startTheGame} from "../../service/back-end-com.js";
+import {getPackInfo, saveRoundDuration, startTheGame, validatePack} from "../../service/back-end-com.js";
 
 const {invoke} = window.__TAURI__.tauri;
-const {open} = window.__TAURI__.dialog;
+const {open, ask} = window.__TAURI__.dialog;
 
 let countDownModal = document.querySelector("#first-player-modal");
 
@@ -24,8 +24,37 @@ export async function openPackInfoModal() {
         console.info("Selected game package path: ", filePath);
     }
 
-    await getPackInfo(filePath)
+    const validation = await validatePack(filePath).catch((error) => {
+        console.error("Pack validation failed:", error);
+        openPackErrorModel(error);
+        return null;
+    });
+    if (validation === null) {
+        return;
+    }
+    if (!validation.loadable) {
+        openPackErrorModel({
+            path: validation.path,
+            cause: "Pack has errors",
+            details: describeIssues(validation.issues),
+        });
+        return;
+    }
+
+    const acceptWarnings = validation.issues.length > 0;
+    if (acceptWarnings) {
+        const accepted = await ask(
+            "Pack has warnings:\n" + describeIssues(validation.issues) + "\n\nLoad it anyway?",
+            {title: "Pack warnings", type: "warning"}
+        );
+        if (!accepted) {
+            return;
+        }
+    }
+
+    await getPackInfo(filePath, acceptWarnings)
         .then((packInfo) => {
+            setPackWarnings(validation.issues);
             setPackName(packInfo.packName);
             setPackAuthor(packInfo.packAuthor);
             setPackRounds(packInfo.packRounds);
@@ -42,6 +71,25 @@ export async function openPackInfoModal() {
         });
 }
 
+function describeIssue(issue) {
+    return issue.severity + ": " + issue.cause + ". " + issue.location;
+}
+
+function describeIssues(issues) {
+    return issues.map(describeIssue).join("\n");
+}
+
+function setPackWarnings(issues) {
+    const packWarningsElement = document.querySelector("#pack-warnings");
+    packWarningsElement.innerHTML = "";
+
+    issues.forEach((issue) => {
+        let li = document.createElement("li");
+        li.innerText = describeIssue(issue);
+        packWarningsElement.appendChild(li);
+    });
+}
+
 function setPackTopicList(packTopicList) {
     const packTopicListElement = document.querySelector("#topic-list");
     packTopicListElement.innerHTML = "";
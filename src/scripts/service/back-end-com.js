@@ -1,4 +1,5 @@
 const {invoke} = window.__TAURI__.tauri;
+const {listen} = window.__TAURI__.event;
 
 export async function setHubType(hubType) {
     return await invoke("set_hub_type", {hubType});
@@ -34,19 +35,47 @@ export async function savePlayers(playersList) {
     await invoke("save_players", {players: playersList});
 }
 
-export async function getPackInfo(path) {
-    return invoke("get_pack_info", {path: path});
+export async function saveTeams(teams) {
+    return await invoke("save_teams", {teams});
+}
+
+export async function fetchTeams() {
+    return await invoke("fetch_teams");
+}
+
+export async function validatePack(path) {
+    return invoke("validate_pack", {path: path});
+}
+
+export async function getPackInfo(path, acceptWarnings) {
+    return invoke("get_pack_info", {path: path, acceptWarnings: acceptWarnings});
 }
 
 export async function saveRoundDuration(roundDurationMinutes) {
     console.log("Round duration: " + roundDurationMinutes);
-    invoke("save_round_duration", {durationMin: roundDurationMinutes});
+    invoke("save_round_duration", {roundMinutes: parseInt(roundDurationMinutes)});
+}
+
+export async function saveAnswerDuration(answerSeconds) {
+    return await invoke("save_answer_duration", {answerSeconds: parseInt(answerSeconds)});
+}
+
+export async function saveFalseStartPenalty(penalty) {
+    return await invoke("save_false_start_penalty", {penalty});
+}
+
+export async function saveRuleSet(ruleSet) {
+    return await invoke("save_rule_set", {ruleSet});
 }
 
 export function startTheGame() {
     return invoke("start_the_game");
 }
 
+export async function resumeLastGame() {
+    return await invoke("resume_last_game");
+}
+
 export async function fetchPlayers() {
     return await invoke("fetch_players");
 }
@@ -56,8 +85,16 @@ export async function fetchRound() {
     return await invoke("fetch_round");
 }
 
-export async function getQuestionData(topic, price) {
-    return await invoke("get_question_data", {topic: topic, price: price});
+export async function getQuestionData(id) {
+    return await invoke("get_question_data", {id: id});
+}
+
+export async function revealAnswerScene() {
+    return await invoke("reveal_answer_scene");
+}
+
+export async function fetchHostNotes() {
+    return await invoke("fetch_host_notes");
 }
 
 export async function hasNextQuestion() {
@@ -81,10 +118,46 @@ export async function sendPipVictim(victimId) {
     return await invoke("send_pip_victim", {victimId: victimId});
 }
 
+export async function fetchAuctionState() {
+    return await invoke("fetch_auction_state");
+}
+
+export async function placeAuctionBid(playerId, bidType, amount) {
+    return await invoke("place_auction_bid", {playerId: playerId, bidType: bidType, amount: amount});
+}
+
+export async function fetchFinalRound() {
+    return await invoke("fetch_final_round");
+}
+
+export async function eliminateFinalTheme(playerId, themeIndex) {
+    return await invoke("eliminate_final_theme", {playerId: playerId, themeIndex: themeIndex});
+}
+
+export async function placeFinalWager(playerId, wager) {
+    return await invoke("place_final_wager", {playerId: playerId, wager: wager});
+}
+
+export async function getFinalQuestion() {
+    return await invoke("get_final_question");
+}
+
+export async function submitFinalAnswer(playerId, answer) {
+    return await invoke("submit_final_answer", {playerId: playerId, answer: answer});
+}
+
+export async function judgeFinalAnswer(playerId, answeredCorrectly) {
+    return await invoke("judge_final_answer", {playerId: playerId, answeredCorrectly: answeredCorrectly});
+}
+
 export async function getActivePlayerId() {
     return await invoke("get_active_player_id");
 }
 
+export async function getQuestionChooserId() {
+    return await invoke("get_question_chooser_id");
+}
+
 export async function allowAnswer() {
     return await invoke("allow_answer");
 }
@@ -93,14 +166,98 @@ export async function finishQuestionPrematurely(topic, price) {
     return await invoke("finish_question_prematurely", {topic: topic, price: price});
 }
 
-export async function waitForFirstClick() {
-    return await invoke("get_fastest_click");
+export async function listenBuzzerWinner(handler) {
+    return await listen("buzzer-winner", (event) => handler(event.payload));
+}
+
+export async function listenAnswerTimeExpired(handler) {
+    return await listen("answer-time-expired", (event) => handler(event.payload));
+}
+
+export async function listenPlayersChanged(handler) {
+    return await listen("players-changed", (event) => handler(event.payload));
+}
+
+export async function listenActivePlayerChanged(handler) {
+    return await listen("active-player-changed", (event) => handler(event.payload));
+}
+
+export async function listenGameStateChanged(handler) {
+    return await listen("game-state-changed", (event) => handler(event.payload));
+}
+
+export async function listenRoundProgressChanged(handler) {
+    return await listen("round-progress-changed", (event) => handler(event.payload));
+}
+
+export async function fetchAnswerTimer() {
+    return await invoke("fetch_answer_timer");
+}
+
+export async function extendAnswerTimer(extraSeconds) {
+    return await invoke("extend_answer_timer", {extraSeconds: extraSeconds});
+}
+
+export async function cancelAnswerTimer() {
+    return await invoke("cancel_answer_timer");
+}
+
+export async function fetchGameHistory() {
+    return await invoke("fetch_game_history");
+}
+
+export async function undoLastAction() {
+    return await invoke("undo_last_action");
+}
+
+export async function redoLastAction() {
+    return await invoke("redo_last_action");
 }
 
 export async function isAllowAnswerRequired() {
     return await invoke("is_allow_answer_required");
 }
 
+export async function fetchRoundTimer() {
+    return await invoke("fetch_round_timer");
+}
+
+export async function setRoundTimerPaused(paused) {
+    return await invoke("set_round_timer_paused", {paused: paused});
+}
+
+export async function startTieBreak(source) {
+    return await invoke("start_tie_break", {source: source});
+}
+
+export async function fetchTieBreak() {
+    return await invoke("fetch_tie_break");
+}
+
+export async function fetchGameStats() {
+    return await invoke("fetch_game_stats");
+}
+
+export async function adjustPlayerScore(playerId, adjustmentType, amount, reason) {
+    return await invoke("adjust_player_score", {playerId, adjustmentType, amount, reason});
+}
+
+export async function exportGameJournal() {
+    return await invoke("export_game_journal");
+}
+
+export async function replayGameJournal(path) {
+    return await invoke("replay_game_journal", {path});
+}
+
+export async function fetchGameState() {
+    return await invoke("fetch_game_state");
+}
+
+export async function fetchStateTransitions() {
+    return await invoke("fetch_state_transitions");
+}
+
 export async function fetchRoundStats() {
     return await invoke("fetch_round_stats");
     return {